@@ -16,6 +16,105 @@ pub enum Error {
         #[from]
         serde_json::Error,
     ),
+    /// Error returned by the `http` crate when converting to or from its types.
+    #[cfg(feature = "http-interop")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http-interop")))]
+    #[error("{0}")]
+    HttpInterop(
+        #[source]
+        #[from]
+        http::Error,
+    ),
+    /// Error returned by `ciborium` during CBOR serialization.
+    #[cfg(feature = "cbor")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cbor")))]
+    #[error("{0}")]
+    CborSerError(
+        #[source]
+        #[from]
+        ciborium::ser::Error<std::io::Error>,
+    ),
+    /// Error returned by `ciborium` during CBOR deserialization.
+    #[cfg(feature = "cbor")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cbor")))]
+    #[error("{0}")]
+    CborDeError(
+        #[source]
+        #[from]
+        ciborium::de::Error<std::io::Error>,
+    ),
+    /// Error returned by `rmp-serde` during MessagePack serialization.
+    #[cfg(feature = "msgpack")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "msgpack")))]
+    #[error("{0}")]
+    MsgpackSerError(
+        #[source]
+        #[from]
+        rmp_serde::encode::Error,
+    ),
+    /// Error returned by `rmp-serde` during MessagePack deserialization.
+    #[cfg(feature = "msgpack")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "msgpack")))]
+    #[error("{0}")]
+    MsgpackDeError(
+        #[source]
+        #[from]
+        rmp_serde::decode::Error,
+    ),
+    /// Error returned by `serde` when encoding a query string or form body.
+    #[cfg(feature = "urlencoded")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "urlencoded")))]
+    #[error("{0}")]
+    UrlEncodedSerError(
+        #[source]
+        #[from]
+        serde_html_form::ser::Error,
+    ),
+    /// Error returned by `serde` when decoding a query string or form body.
+    #[cfg(feature = "urlencoded")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "urlencoded")))]
+    #[error("{0}")]
+    UrlEncodedDeError(
+        #[source]
+        #[from]
+        serde_html_form::de::Error,
+    ),
+    /// Error returned by `prost` when decoding a protobuf message.
+    #[cfg(feature = "protobuf")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "protobuf")))]
+    #[error("{0}")]
+    ProstDecodeError(
+        #[source]
+        #[from]
+        prost::DecodeError,
+    ),
+    /// The server responded with a status code that is not a success.
+    ///
+    /// Returned by [`Response::error_for_status`](crate::http::Response::error_for_status).
+    #[cfg(feature = "http")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+    #[error("HTTP status {status}")]
+    Http {
+        /// The status code of the response.
+        status: crate::http::StatusCode,
+        /// The status message of the response.
+        status_text: String,
+        /// The response body, kept for diagnostics.
+        body: String,
+    },
+    /// The server responded with an
+    /// [RFC 7807](https://www.rfc-editor.org/rfc/rfc7807) `application/problem+json` body.
+    ///
+    /// Returned by [`Response::error_for_status`](crate::http::Response::error_for_status).
+    #[cfg(all(feature = "http", feature = "json"))]
+    #[cfg_attr(docsrs, doc(cfg(all(feature = "http", feature = "json"))))]
+    #[error("HTTP status {status}: {details}")]
+    Problem {
+        /// The status code of the response.
+        status: crate::http::StatusCode,
+        /// The decoded problem details.
+        details: Box<crate::http::ProblemDetails>,
+    },
     /// Error returned by this crate
     #[error("{0}")]
     GlooError(String),
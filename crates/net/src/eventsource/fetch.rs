@@ -0,0 +1,214 @@
+//! A Server-Sent Events client built on the `fetch` API.
+//!
+//! The browser's `EventSource` can only send `GET` requests without custom headers.
+//! [`FetchEventSource`] sends any [`RequestBuilder`] instead, so it can use `POST`, an
+//! `Authorization` header, a request body, and so on. The `text/event-stream` response is parsed
+//! by this crate.
+//!
+//! Like the browser's `EventSource`, it reconnects when the connection is lost, waiting for the
+//! reconnection time requested by the server and sending the ID of the last received event in the
+//! `Last-Event-ID` header. It stops for good when the server responds with anything other than a
+//! `200 OK` `text/event-stream` response.
+//!
+//! # Example
+//!
+//! ```rust
+//! use gloo_net::eventsource::fetch::FetchEventSource;
+//! use gloo_net::http::Request;
+//! use wasm_bindgen_futures::spawn_local;
+//! use futures::StreamExt;
+//!
+//! # macro_rules! console_log {
+//! #    ($($expr:expr),*) => {{}};
+//! # }
+//! # fn no_run() {
+//! let request = Request::post("http://api.example.com/events")
+//!     .header("Authorization", "Bearer token")
+//!     .body("{\"topic\":\"news\"}")
+//!     .unwrap();
+//! let mut es = FetchEventSource::from_request(request).unwrap();
+//!
+//! spawn_local(async move {
+//!     while let Some(Ok((event_type, msg))) = es.next().await {
+//!         console_log!(format!("1. {}: {:?}", event_type, msg.data()))
+//!     }
+//!     console_log!("EventSource Closed");
+//! })
+//! # }
+//! ```
+
+use crate::eventsource::{EventSourceError, SseEvent, SseParser};
+use crate::http::body::BodyStream;
+use crate::http::{Request, RequestBuilder, Response};
+use crate::timer::timeout;
+use crate::{js_to_error, Error};
+use futures_channel::mpsc;
+use futures_core::Stream;
+use std::fmt;
+use std::future::poll_fn;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{AbortController, AbortSignal, MessageEvent};
+
+/// The reconnection time used until the server sends a `retry` field.
+const DEFAULT_RETRY: Duration = Duration::from_secs(3);
+
+/// A Server-Sent Events connection made with `fetch`.
+///
+/// This is a stream of `(event_type, message)` pairs, like the subscriptions of
+/// [`EventSource`](crate::eventsource::futures::EventSource), but covering every event type. The
+/// stream ends after yielding [`EventSourceError::ConnectionError`] if the connection fails for
+/// good. Dropping it closes the connection.
+pub struct FetchEventSource {
+    message_receiver: mpsc::UnboundedReceiver<Result<(String, MessageEvent), EventSourceError>>,
+    abort: AbortController,
+}
+
+impl FetchEventSource {
+    /// Open a connection by sending the request built by `request`, reconnecting as needed.
+    ///
+    /// The `Accept: text/event-stream` and `Cache-Control: no-cache` headers are added to the
+    /// request. It is sent with [`Request::send`], so it honours the installed
+    /// [`Transport`](crate::http::Transport).
+    pub fn new(request: RequestBuilder) -> Result<Self, Error> {
+        Self::from_request(request.build()?)
+    }
+
+    /// Open a connection by sending `request`, reconnecting as needed.
+    ///
+    /// Use this for requests with a body, which are built by [`RequestBuilder::body`]. See
+    /// [`FetchEventSource::new`].
+    pub fn from_request(request: Request) -> Result<Self, Error> {
+        let template = web_sys::Request::from(request);
+        let abort = AbortController::new().map_err(js_to_error)?;
+        let (sender, message_receiver) = mpsc::unbounded();
+
+        spawn_local(run(template, abort.signal(), sender));
+
+        Ok(Self {
+            message_receiver,
+            abort,
+        })
+    }
+
+    /// Closes the connection.
+    pub fn close(self) {}
+}
+
+impl Drop for FetchEventSource {
+    fn drop(&mut self) {
+        self.abort.abort();
+    }
+}
+
+impl fmt::Debug for FetchEventSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FetchEventSource")
+            .field("closed", &self.abort.signal().aborted())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Stream for FetchEventSource {
+    type Item = Result<(String, MessageEvent), EventSourceError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.message_receiver).poll_next(cx)
+    }
+}
+
+type Sender = mpsc::UnboundedSender<Result<(String, MessageEvent), EventSourceError>>;
+
+async fn run(template: web_sys::Request, signal: AbortSignal, sender: Sender) {
+    let mut parser = SseParser::new();
+    loop {
+        let request = match connect_request(&template, &signal, parser.last_event_id()) {
+            Ok(request) => Request::from(request),
+            Err(_) => {
+                let _ = sender.unbounded_send(Err(EventSourceError::ConnectionError));
+                return;
+            }
+        };
+
+        match request.send().await {
+            Ok(response) if is_event_stream(&response) => {
+                let origin = web_sys::Url::new(&response.url())
+                    .map(|url| url.origin())
+                    .unwrap_or_default();
+                let mut body = match BodyStream::new(&response) {
+                    Ok(body) => body,
+                    Err(_) => {
+                        let _ = sender.unbounded_send(Err(EventSourceError::ConnectionError));
+                        return;
+                    }
+                };
+                while let Some(Ok(chunk)) = poll_fn(|cx| Pin::new(&mut body).poll_next(cx)).await {
+                    for event in parser.push(&chunk) {
+                        let message = message_event(&event, &origin);
+                        if sender.unbounded_send(Ok((event.event, message))).is_err() {
+                            return;
+                        }
+                    }
+                }
+                parser.reset();
+            }
+            Ok(_) => {
+                let _ = sender.unbounded_send(Err(EventSourceError::ConnectionError));
+                return;
+            }
+            // network errors are retried
+            Err(_) => {}
+        }
+
+        if signal.aborted() || sender.is_closed() {
+            return;
+        }
+        let retry = parser.retry().unwrap_or(DEFAULT_RETRY);
+        timeout(retry).await;
+    }
+}
+
+/// Clones the template request, adding the event stream headers and the abort signal.
+fn connect_request(
+    template: &web_sys::Request,
+    signal: &AbortSignal,
+    last_event_id: &str,
+) -> Result<web_sys::Request, Error> {
+    let request = web_sys::Request::clone(template).map_err(js_to_error)?;
+    let headers = request.headers();
+    headers
+        .set("Accept", "text/event-stream")
+        .map_err(js_to_error)?;
+    headers
+        .set("Cache-Control", "no-cache")
+        .map_err(js_to_error)?;
+    if !last_event_id.is_empty() {
+        headers
+            .set("Last-Event-ID", last_event_id)
+            .map_err(js_to_error)?;
+    }
+
+    let mut init = web_sys::RequestInit::new();
+    init.signal(Some(signal));
+    web_sys::Request::new_with_request_and_init(&request, &init).map_err(js_to_error)
+}
+
+fn is_event_stream(response: &Response) -> bool {
+    response.status() == 200
+        && response
+            .headers()
+            .content_type()
+            .map_or(false, |mime| mime.essence_str() == "text/event-stream")
+}
+
+fn message_event(event: &SseEvent, origin: &str) -> MessageEvent {
+    let mut init = web_sys::MessageEventInit::new();
+    init.data(&JsValue::from_str(&event.data))
+        .last_event_id(&event.id)
+        .origin(origin);
+    MessageEvent::new_with_event_init_dict(&event.event, &init)
+        .expect("constructing a MessageEvent should not fail")
+}
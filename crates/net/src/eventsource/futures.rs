@@ -40,8 +40,12 @@ use futures_channel::mpsc;
 use futures_core::{ready, Stream};
 use gloo_utils::errors::JsError;
 use pin_project::{pin_project, pinned_drop};
+#[cfg(feature = "json")]
+use serde::de::DeserializeOwned;
 use std::fmt;
 use std::fmt::Formatter;
+#[cfg(feature = "json")]
+use std::marker::PhantomData;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 use wasm_bindgen::prelude::*;
@@ -86,6 +90,37 @@ impl fmt::Debug for EventSourceSubscription {
     }
 }
 
+/// Configuration for an [`EventSource`], created by [`EventSource::builder`].
+#[derive(Clone, Debug)]
+pub struct EventSourceBuilder {
+    url: String,
+    with_credentials: bool,
+}
+
+impl EventSourceBuilder {
+    /// Whether to send credentials (cookies, TLS client certificates and authorization headers)
+    /// with cross-origin requests. Defaults to `false`.
+    ///
+    /// See the [MDN Documentation](https://developer.mozilla.org/en-US/docs/Web/API/EventSource/withCredentials)
+    /// to learn more.
+    pub fn with_credentials(mut self, with_credentials: bool) -> Self {
+        self.with_credentials = with_credentials;
+        self
+    }
+
+    /// Establish the EventSource.
+    ///
+    /// This fails in the same cases as [`EventSource::new`].
+    pub fn build(self) -> Result<EventSource, JsError> {
+        let mut init = web_sys::EventSourceInit::new();
+        init.with_credentials(self.with_credentials);
+        let es = web_sys::EventSource::new_with_event_source_init_dict(&self.url, &init)
+            .map_err(js_to_js_error)?;
+
+        Ok(EventSource { es })
+    }
+}
+
 impl EventSource {
     /// Establish an EventSource.
     ///
@@ -101,6 +136,25 @@ impl EventSource {
         Ok(Self { es })
     }
 
+    /// Configure an EventSource for the given url before establishing it.
+    ///
+    /// ```rust
+    /// use gloo_net::eventsource::futures::EventSource;
+    ///
+    /// # fn no_run() {
+    /// let es = EventSource::builder("http://api.example.com/events")
+    ///     .with_credentials(true)
+    ///     .build()
+    ///     .unwrap();
+    /// # }
+    /// ```
+    pub fn builder(url: impl Into<String>) -> EventSourceBuilder {
+        EventSourceBuilder {
+            url: url.into(),
+            with_credentials: false,
+        }
+    }
+
     /// Subscribes to listening for a specific type of event.
     ///
     /// All events for this type are streamed back given the subscription
@@ -159,6 +213,106 @@ impl EventSource {
         })
     }
 
+    /// Subscribes to a specific type of event whose data is JSON, decoding each message as `T`.
+    ///
+    /// A message that fails to decode yields an [`EventSourceError::DecodeError`] and the stream
+    /// continues with the next message. See [`EventSource::subscribe`] for how event types are
+    /// matched.
+    ///
+    /// ```rust
+    /// use gloo_net::eventsource::futures::EventSource;
+    /// use futures::StreamExt;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Price {
+    ///     symbol: String,
+    ///     value: f64,
+    /// }
+    ///
+    /// # async fn no_run() {
+    /// let mut es = EventSource::new("http://api.example.com/prices").unwrap();
+    /// let mut prices = es.subscribe_json::<Price>("price").unwrap();
+    ///
+    /// while let Some(price) = prices.next().await {
+    ///     match price {
+    ///         Ok(price) => { /* ... */ }
+    ///         Err(e) => { /* ... */ }
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    #[cfg(feature = "json")]
+    pub fn subscribe_json<T: DeserializeOwned>(
+        &mut self,
+        event_type: impl Into<String>,
+    ) -> Result<JsonSubscription<T>, JsError> {
+        Ok(JsonSubscription {
+            subscription: self.subscribe(event_type)?,
+            _marker: PhantomData,
+        })
+    }
+
+    /// A stream of the connection state of the EventSource.
+    ///
+    /// It yields the current state first, then every change of state: the browser reconnecting
+    /// moves it back to [`State::Connecting`], and a successful (re)connection to
+    /// [`State::Open`]. The stream ends after yielding [`State::Closed`], which happens when the
+    /// EventSource is closed or the browser gives up reconnecting.
+    ///
+    /// ```rust
+    /// use gloo_net::eventsource::{futures::EventSource, State};
+    /// use futures::StreamExt;
+    ///
+    /// # async fn no_run() {
+    /// let mut es = EventSource::new("http://api.example.com/events").unwrap();
+    /// let mut states = es.state_changes().unwrap();
+    ///
+    /// while let Some(state) = states.next().await {
+    ///     match state {
+    ///         State::Connecting => { /* show a spinner */ }
+    ///         State::Open => { /* show as connected */ }
+    ///         State::Closed => { /* offer to reconnect */ }
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn state_changes(&mut self) -> Result<StateChanges, JsError> {
+        let (state_sender, state_receiver) = mpsc::unbounded();
+        let _ = state_sender.unbounded_send(self.state());
+
+        let open_callback: Closure<dyn FnMut(web_sys::Event)> = {
+            let sender = state_sender.clone();
+            Closure::wrap(Box::new(move |_e: web_sys::Event| {
+                let _ = sender.unbounded_send(State::Open);
+            }) as Box<dyn FnMut(web_sys::Event)>)
+        };
+
+        self.es
+            .add_event_listener_with_callback("open", open_callback.as_ref().unchecked_ref())
+            .map_err(js_to_js_error)?;
+
+        let error_callback: Closure<dyn FnMut(web_sys::Event)> = {
+            let es = self.es.clone();
+            Closure::wrap(Box::new(move |_e: web_sys::Event| {
+                let _ = state_sender.unbounded_send(ready_state_to_state(es.ready_state()));
+            }) as Box<dyn FnMut(web_sys::Event)>)
+        };
+
+        self.es
+            .add_event_listener_with_callback("error", error_callback.as_ref().unchecked_ref())
+            .map_err(js_to_js_error)?;
+
+        Ok(StateChanges {
+            es: self.es.clone(),
+            open_callback,
+            error_callback,
+            state_receiver,
+            last: None,
+        })
+    }
+
     /// Closes the EventSource.
     ///
     /// See the [MDN Documentation](https://developer.mozilla.org/en-US/docs/Web/API/EventSource/close#parameters)
@@ -178,13 +332,16 @@ impl EventSource {
 
     /// The current state of the EventSource.
     pub fn state(&self) -> State {
-        let ready_state = self.es.ready_state();
-        match ready_state {
-            0 => State::Connecting,
-            1 => State::Open,
-            2 => State::Closed,
-            _ => unreachable!(),
-        }
+        ready_state_to_state(self.es.ready_state())
+    }
+}
+
+fn ready_state_to_state(ready_state: u16) -> State {
+    match ready_state {
+        0 => State::Connecting,
+        1 => State::Open,
+        2 => State::Closed,
+        _ => unreachable!(),
     }
 }
 
@@ -217,6 +374,104 @@ impl Stream for EventSourceSubscription {
     }
 }
 
+/// A subscription to an event type whose messages are decoded from JSON.
+///
+/// Returned by [`EventSource::subscribe_json`].
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+#[cfg(feature = "json")]
+#[pin_project]
+pub struct JsonSubscription<T> {
+    #[pin]
+    subscription: EventSourceSubscription,
+    _marker: PhantomData<fn() -> T>,
+}
+
+#[cfg(feature = "json")]
+impl<T> fmt::Debug for JsonSubscription<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonSubscription")
+            .field("subscription", &self.subscription)
+            .finish()
+    }
+}
+
+#[cfg(feature = "json")]
+impl<T: DeserializeOwned> Stream for JsonSubscription<T> {
+    type Item = Result<T, EventSourceError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let item = ready!(self.project().subscription.poll_next(cx));
+        Poll::Ready(item.map(|item| item.and_then(|(_, msg)| decode_json(&msg))))
+    }
+}
+
+#[cfg(feature = "json")]
+fn decode_json<T: DeserializeOwned>(msg: &MessageEvent) -> Result<T, EventSourceError> {
+    let data = msg
+        .data()
+        .as_string()
+        .ok_or_else(|| EventSourceError::DecodeError("message data is not a string".into()))?;
+    serde_json::from_str(&data).map_err(|e| EventSourceError::DecodeError(e.to_string()))
+}
+
+/// A stream of the connection state of an [`EventSource`].
+///
+/// Returned by [`EventSource::state_changes`].
+#[pin_project(PinnedDrop)]
+pub struct StateChanges {
+    es: web_sys::EventSource,
+    open_callback: Closure<dyn FnMut(web_sys::Event)>,
+    error_callback: Closure<dyn FnMut(web_sys::Event)>,
+    #[pin]
+    state_receiver: mpsc::UnboundedReceiver<State>,
+    last: Option<State>,
+}
+
+impl fmt::Debug for StateChanges {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StateChanges")
+            .field("event_source", &self.es)
+            .field("last", &self.last)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Stream for StateChanges {
+    type Item = State;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        loop {
+            if *this.last == Some(State::Closed) {
+                return Poll::Ready(None);
+            }
+            match ready!(this.state_receiver.as_mut().poll_next(cx)) {
+                // several listeners may report the same state
+                Some(state) if Some(state) == *this.last => continue,
+                Some(state) => {
+                    *this.last = Some(state);
+                    return Poll::Ready(Some(state));
+                }
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+#[pinned_drop]
+impl PinnedDrop for StateChanges {
+    fn drop(self: Pin<&mut Self>) {
+        let _ = self.es.remove_event_listener_with_callback(
+            "open",
+            self.open_callback.as_ref().unchecked_ref(),
+        );
+        let _ = self.es.remove_event_listener_with_callback(
+            "error",
+            self.error_callback.as_ref().unchecked_ref(),
+        );
+    }
+}
+
 #[pinned_drop]
 impl PinnedDrop for EventSourceSubscription {
     fn drop(self: Pin<&mut Self>) {
@@ -253,6 +508,40 @@ mod tests {
         assert_eq!(requests.next().await.unwrap().unwrap().0, "request");
     }
 
+    #[cfg(feature = "json")]
+    #[wasm_bindgen_test]
+    fn json_messages_are_decoded() {
+        let message = |data: &str| {
+            let mut init = web_sys::MessageEventInit::new();
+            init.data(&JsValue::from_str(data));
+            MessageEvent::new_with_event_init_dict("update", &init).unwrap()
+        };
+
+        assert_eq!(decode_json::<Vec<u32>>(&message("[1, 2]")), Ok(vec![1, 2]));
+        assert!(matches!(
+            decode_json::<Vec<u32>>(&message("{}")),
+            Err(EventSourceError::DecodeError(_))
+        ));
+    }
+
+    #[wasm_bindgen_test]
+    async fn state_changes_works() {
+        let sse_echo_server_url =
+            option_env!("SSE_ECHO_SERVER_URL").expect("Did you set SSE_ECHO_SERVER_URL?");
+
+        let mut es = EventSource::builder(sse_echo_server_url)
+            .with_credentials(true)
+            .build()
+            .unwrap();
+        let mut states = es.state_changes().unwrap();
+
+        assert_eq!(states.next().await, Some(State::Connecting));
+        assert_eq!(states.next().await, Some(State::Open));
+        es.close();
+        assert_eq!(states.next().await, Some(State::Closed));
+        assert_eq!(states.next().await, None);
+    }
+
     #[wasm_bindgen_test]
     async fn eventsource_connect_failure_works() {
         let mut es = EventSource::new("rubbish").unwrap();
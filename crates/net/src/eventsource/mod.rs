@@ -2,8 +2,17 @@
 //!
 //! This API is provided in the following flavors:
 //! - [Futures API][futures]
+//! - [`fetch` based client][fetch], which supports custom requests
+//!
+//! The `text/event-stream` format can also be parsed on its own with [`SseParser`].
 
+#[cfg(feature = "http")]
+#[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+pub mod fetch;
 pub mod futures;
+mod parser;
+
+pub use parser::{SseEvent, SseParser};
 
 use std::fmt;
 
@@ -28,12 +37,20 @@ pub enum State {
 pub enum EventSourceError {
     /// The `error` event
     ConnectionError,
+    /// The data of a message could not be decoded.
+    ///
+    /// Holds the error message of the decoder. Unlike [`EventSourceError::ConnectionError`],
+    /// this does not end the stream.
+    DecodeError(String),
 }
 
 impl fmt::Display for EventSourceError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             EventSourceError::ConnectionError => write!(f, "EventSource connection failed"),
+            EventSourceError::DecodeError(e) => {
+                write!(f, "failed to decode EventSource message: {e}")
+            }
         }
     }
 }
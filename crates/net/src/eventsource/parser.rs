@@ -0,0 +1,344 @@
+//! Incremental parser for the `text/event-stream` format.
+//!
+//! See the [HTML Standard](https://html.spec.whatwg.org/multipage/server-sent-events.html#event-stream-interpretation)
+//! for the format.
+
+use std::time::Duration;
+
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// An event parsed from a `text/event-stream` by [`SseParser`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SseEvent {
+    /// The event type, `message` if the event had no `event` field.
+    pub event: String,
+    /// The data of the event. Multiple `data` fields are joined with newlines.
+    pub data: String,
+    /// The last event ID at the time the event was dispatched.
+    pub id: String,
+    /// The reconnection time, if a `retry` field was received as part of this event.
+    pub retry: Option<Duration>,
+}
+
+/// An incremental parser for the `text/event-stream` format used by Server-Sent Events.
+///
+/// Bytes are pushed in as they are received, in chunks of any size, and the events they complete
+/// are returned. The parser does not depend on a browser, so it can be used in workers, on
+/// other transports and in native tests. It follows the
+/// [HTML Standard](https://html.spec.whatwg.org/multipage/server-sent-events.html#event-stream-interpretation),
+/// including its handling of a leading byte order mark, `CR`, `LF` and `CRLF` line endings,
+/// comments and events without data.
+///
+/// # Example
+///
+/// ```
+/// use gloo_net::eventsource::SseParser;
+///
+/// let mut parser = SseParser::new();
+/// assert!(parser.push(b"event: update\ndata: hello").is_empty());
+///
+/// let events = parser.push(b"\ndata: world\n\n");
+/// assert_eq!(events[0].event, "update");
+/// assert_eq!(events[0].data, "hello\nworld");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SseParser {
+    buffer: Vec<u8>,
+    bom_checked: bool,
+    /// The previous line ended with a `CR`, so a leading `LF` belongs to that line ending.
+    skip_lf: bool,
+    event_type: String,
+    data: String,
+    event_retry: Option<Duration>,
+    /// The latest `id` field, which becomes the last event ID when the event is dispatched.
+    id_buffer: String,
+    last_event_id: String,
+    retry: Option<Duration>,
+}
+
+impl SseParser {
+    /// Create a new parser at the start of a stream.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The last event ID, to be sent in the `Last-Event-ID` header when reconnecting.
+    ///
+    /// It is updated when an event is dispatched, even one without data.
+    pub fn last_event_id(&self) -> &str {
+        &self.last_event_id
+    }
+
+    /// The latest reconnection time sent by the server with a `retry` field.
+    pub fn retry(&self) -> Option<Duration> {
+        self.retry
+    }
+
+    /// Ends the current stream, discarding any partially received event.
+    ///
+    /// The last event ID and the reconnection time are kept, so the parser can be reused for the
+    /// stream of the next connection.
+    pub fn reset(&mut self) {
+        self.buffer.clear();
+        self.bom_checked = false;
+        self.skip_lf = false;
+        self.event_type.clear();
+        self.data.clear();
+        self.event_retry = None;
+        self.id_buffer.clone_from(&self.last_event_id);
+    }
+
+    /// Parses the given bytes, returning the events they complete.
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend_from_slice(bytes);
+
+        if !self.bom_checked {
+            if self.buffer.len() < BOM.len() && BOM.starts_with(&self.buffer) {
+                // wait for the rest of a possible byte order mark
+                return Vec::new();
+            }
+            if self.buffer.starts_with(BOM) {
+                self.buffer.drain(..BOM.len());
+            }
+            self.bom_checked = true;
+        }
+
+        let mut events = Vec::new();
+        let mut start = 0;
+        while start < self.buffer.len() {
+            if self.skip_lf {
+                self.skip_lf = false;
+                if self.buffer[start] == b'\n' {
+                    start += 1;
+                    continue;
+                }
+            }
+
+            let end = match self.buffer[start..]
+                .iter()
+                .position(|&b| b == b'\n' || b == b'\r')
+            {
+                Some(index) => start + index,
+                None => break,
+            };
+            self.skip_lf = self.buffer[end] == b'\r';
+
+            let line = String::from_utf8_lossy(&self.buffer[start..end]).into_owned();
+            if let Some(event) = self.process_line(&line) {
+                events.push(event);
+            }
+            start = end + 1;
+        }
+        self.buffer.drain(..start);
+
+        events
+    }
+
+    fn process_line(&mut self, line: &str) -> Option<SseEvent> {
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "event" => self.event_type = value.to_string(),
+            "data" => {
+                self.data.push_str(value);
+                self.data.push('\n');
+            }
+            "id" if !value.contains('\0') => self.id_buffer = value.to_string(),
+            "retry" if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) => {
+                if let Ok(millis) = value.parse() {
+                    let retry = Duration::from_millis(millis);
+                    self.retry = Some(retry);
+                    self.event_retry = Some(retry);
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn dispatch(&mut self) -> Option<SseEvent> {
+        self.last_event_id.clone_from(&self.id_buffer);
+        let event_type = std::mem::take(&mut self.event_type);
+        let mut data = std::mem::take(&mut self.data);
+        let retry = self.event_retry.take();
+        if data.is_empty() {
+            return None;
+        }
+        data.pop();
+
+        Some(SseEvent {
+            event: if event_type.is_empty() {
+                "message".to_string()
+            } else {
+                event_type
+            },
+            data,
+            id: self.last_event_id.clone(),
+            retry,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(event: &str, data: &str, id: &str) -> SseEvent {
+        SseEvent {
+            event: event.to_string(),
+            data: data.to_string(),
+            id: id.to_string(),
+            retry: None,
+        }
+    }
+
+    /// Parses `input` fed one byte at a time, and checks it gives the same result as in one chunk.
+    fn parse(input: &[u8]) -> Vec<SseEvent> {
+        let whole = SseParser::new().push(input);
+
+        let mut parser = SseParser::new();
+        let bytewise: Vec<_> = input.iter().flat_map(|&b| parser.push(&[b])).collect();
+        assert_eq!(whole, bytewise, "chunking changed the result");
+
+        whole
+    }
+
+    #[test]
+    fn fields_and_comments() {
+        assert_eq!(
+            parse(b": comment\nevent: update\ndata: a\ndata:b\nid: 7\n\ndata: c\n\n"),
+            vec![event("update", "a\nb", "7"), event("message", "c", "7")]
+        );
+    }
+
+    #[test]
+    fn only_one_leading_space_is_removed() {
+        assert_eq!(
+            parse(b"data:  two spaces \ndata:none\n\n"),
+            vec![event("message", " two spaces \nnone", "")]
+        );
+    }
+
+    #[test]
+    fn field_names_are_case_sensitive_and_unknown_fields_ignored() {
+        assert_eq!(
+            parse(b"Data: ignored\nfoo: bar\ndata: kept\n\n"),
+            vec![event("message", "kept", "")]
+        );
+    }
+
+    #[test]
+    fn leading_bom_is_ignored() {
+        assert_eq!(
+            parse(b"\xEF\xBB\xBFdata: a\n\n"),
+            vec![event("message", "a", "")]
+        );
+        // only the first one
+        assert_eq!(parse(b"\xEF\xBB\xBF\xEF\xBB\xBFdata: a\n\n"), vec![]);
+    }
+
+    #[test]
+    fn line_endings() {
+        let expected = vec![event("message", "a\nb", ""), event("message", "c", "")];
+        assert_eq!(parse(b"data: a\ndata: b\n\ndata: c\n\n"), expected);
+        assert_eq!(parse(b"data: a\rdata: b\r\rdata: c\r\r"), expected);
+        assert_eq!(
+            parse(b"data: a\r\ndata: b\r\n\r\ndata: c\r\n\r\n"),
+            expected
+        );
+        assert_eq!(parse(b"data: a\r\ndata: b\r\n\ndata: c\r\r\n"), expected);
+    }
+
+    #[test]
+    fn cr_at_end_of_chunk() {
+        let mut parser = SseParser::new();
+        assert!(parser.push(b"data: a\r").is_empty());
+        assert!(parser.push(b"\n").is_empty());
+        assert_eq!(parser.push(b"\r\n"), vec![event("message", "a", "")]);
+    }
+
+    #[test]
+    fn empty_data_lines() {
+        assert_eq!(parse(b"data\n\n"), vec![event("message", "", "")]);
+        assert_eq!(parse(b"data:\ndata:\n\n"), vec![event("message", "\n", "")]);
+        assert_eq!(
+            parse(b"data: a\ndata\n\n"),
+            vec![event("message", "a\n", "")]
+        );
+    }
+
+    #[test]
+    fn events_without_data_are_not_dispatched() {
+        let mut parser = SseParser::new();
+        assert!(parser.push(b"event: update\nid: 1\n\n").is_empty());
+        assert_eq!(parser.last_event_id(), "1");
+        // the event type does not carry over
+        assert_eq!(
+            parser.push(b"data: a\n\n"),
+            vec![event("message", "a", "1")]
+        );
+    }
+
+    #[test]
+    fn id_field() {
+        assert_eq!(
+            parse(b"id: 1\ndata: a\n\nid: 2\0\ndata: b\n\nid\ndata: c\n\n"),
+            vec![
+                event("message", "a", "1"),
+                event("message", "b", "1"),
+                event("message", "c", ""),
+            ]
+        );
+    }
+
+    #[test]
+    fn retry_field() {
+        let mut parser = SseParser::new();
+        assert!(parser.push(b"retry: 100\n\n").is_empty());
+        assert_eq!(parser.retry(), Some(Duration::from_millis(100)));
+
+        let events = parser.push(b"retry: 2x\nretry: +5\nretry:\ndata: a\n\nretry: 7\ndata: b\n\n");
+        assert_eq!(parser.retry(), Some(Duration::from_millis(7)));
+        assert_eq!(events[0].retry, None);
+        assert_eq!(events[1].retry, Some(Duration::from_millis(7)));
+    }
+
+    #[test]
+    fn incomplete_event_is_discarded_on_reset() {
+        let mut parser = SseParser::new();
+        assert!(parser.push(b"id: 1\n\nevent: x\ndata: partial").is_empty());
+        parser.reset();
+        assert_eq!(
+            parser.push(b"data: b\n\n"),
+            vec![event("message", "b", "1")]
+        );
+    }
+
+    #[test]
+    fn id_of_incomplete_event_is_discarded_on_reset() {
+        let mut parser = SseParser::new();
+        assert!(parser.push(b"id: 1\n\nid: 2\n").is_empty());
+        assert_eq!(parser.last_event_id(), "1");
+        parser.reset();
+        assert_eq!(parser.last_event_id(), "1");
+        assert_eq!(
+            parser.push(b"data: a\n\n"),
+            vec![event("message", "a", "1")]
+        );
+    }
+
+    #[test]
+    fn multi_byte_characters_split_across_chunks() {
+        let input = "data: héllo 🦀\n\n".as_bytes();
+        assert_eq!(parse(input), vec![event("message", "héllo 🦀", "")]);
+    }
+}
@@ -0,0 +1,147 @@
+//! The length-prefixed framing of gRPC-web bodies.
+//!
+//! Each frame is a flag byte, a big-endian 32-bit length and that many bytes. Messages have the
+//! flag `0x00`. Trailers come last in response bodies, with the `0x80` flag and an HTTP/1 style
+//! header block as payload.
+use crate::grpc::GrpcError;
+
+const COMPRESSED: u8 = 0x01;
+const TRAILERS: u8 = 0x80;
+const HEADER_LENGTH: usize = 5;
+/// The longest frame accepted from a server, the default limit of gRPC servers and clients.
+const MAX_FRAME_LENGTH: usize = 4 * 1024 * 1024;
+
+/// A frame of a response body.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Frame {
+    Message(Vec<u8>),
+    Trailers(Vec<(String, String)>),
+}
+
+/// Frames an encoded message, which must fit the 32-bit length.
+pub(crate) fn encode(message: &[u8]) -> Result<Vec<u8>, GrpcError> {
+    let length = u32::try_from(message.len()).map_err(|_| {
+        GrpcError::Protocol(format!(
+            "a message of {} bytes is too long for a frame",
+            message.len()
+        ))
+    })?;
+    let mut frame = Vec::with_capacity(HEADER_LENGTH + message.len());
+    frame.push(0);
+    frame.extend_from_slice(&length.to_be_bytes());
+    frame.extend_from_slice(message);
+    Ok(frame)
+}
+
+/// An incremental decoder of the frames of a response body.
+#[derive(Debug, Default)]
+pub(crate) struct FrameDecoder {
+    buffer: Vec<u8>,
+}
+
+impl FrameDecoder {
+    /// Adds received bytes.
+    pub(crate) fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Removes the next complete frame, if any.
+    pub(crate) fn next_frame(&mut self) -> Result<Option<Frame>, GrpcError> {
+        if self.buffer.len() < HEADER_LENGTH {
+            return Ok(None);
+        }
+        let flags = self.buffer[0];
+        let mut length = [0; 4];
+        length.copy_from_slice(&self.buffer[1..HEADER_LENGTH]);
+        let length = u32::from_be_bytes(length) as usize;
+        if length > MAX_FRAME_LENGTH {
+            return Err(GrpcError::Protocol(format!(
+                "a frame of {length} bytes is longer than the limit of {MAX_FRAME_LENGTH}"
+            )));
+        }
+        if self.buffer.len() < HEADER_LENGTH + length {
+            return Ok(None);
+        }
+
+        if flags & COMPRESSED != 0 {
+            return Err(GrpcError::Protocol(
+                "compressed messages are not supported".to_string(),
+            ));
+        }
+        let payload: Vec<u8> = self
+            .buffer
+            .drain(..HEADER_LENGTH + length)
+            .skip(HEADER_LENGTH)
+            .collect();
+        if flags & TRAILERS != 0 {
+            Ok(Some(Frame::Trailers(parse_trailers(&payload))))
+        } else {
+            Ok(Some(Frame::Message(payload)))
+        }
+    }
+
+    /// Whether part of a frame is left over.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+}
+
+/// Parses `name: value` lines, lowercasing the names.
+fn parse_trailers(payload: &[u8]) -> Vec<(String, String)> {
+    String::from_utf8_lossy(payload)
+        .split("\r\n")
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_are_decoded_across_chunks() {
+        let mut body = encode(b"hello").unwrap();
+        body.extend_from_slice(&encode(b"").unwrap());
+        let trailers = b"grpc-status:0\r\nGrpc-Message: fine \r\n";
+        body.push(TRAILERS);
+        body.extend_from_slice(&(trailers.len() as u32).to_be_bytes());
+        body.extend_from_slice(trailers);
+
+        let mut decoder = FrameDecoder::default();
+        let mut frames = Vec::new();
+        for chunk in body.chunks(3) {
+            decoder.push(chunk);
+            while let Some(frame) = decoder.next_frame().unwrap() {
+                frames.push(frame);
+            }
+        }
+        assert!(decoder.is_empty());
+        assert_eq!(
+            frames,
+            vec![
+                Frame::Message(b"hello".to_vec()),
+                Frame::Message(Vec::new()),
+                Frame::Trailers(vec![
+                    ("grpc-status".to_string(), "0".to_string()),
+                    ("grpc-message".to_string(), "fine".to_string()),
+                ]),
+            ]
+        );
+    }
+
+    #[test]
+    fn compressed_frames_are_rejected() {
+        let mut decoder = FrameDecoder::default();
+        decoder.push(&[COMPRESSED, 0, 0, 0, 1, 42]);
+        assert!(matches!(decoder.next_frame(), Err(GrpcError::Protocol(_))));
+    }
+
+    #[test]
+    fn oversized_frames_are_rejected() {
+        let mut decoder = FrameDecoder::default();
+        let length = (MAX_FRAME_LENGTH as u32 + 1).to_be_bytes();
+        decoder.push(&[0, length[0], length[1], length[2], length[3]]);
+        assert!(matches!(decoder.next_frame(), Err(GrpcError::Protocol(_))));
+    }
+}
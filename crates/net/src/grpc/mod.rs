@@ -0,0 +1,485 @@
+//! A [gRPC-web](https://github.com/grpc/grpc/blob/master/doc/PROTOCOL-WEB.md) client over
+//! `fetch`.
+//!
+//! A [`GrpcClient`] makes unary and server-streaming calls to the services behind a gRPC-web
+//! proxy, such as Envoy, or a server that speaks gRPC-web itself. Messages are encoded and decoded
+//! by a [`MessageCodec`], e.g. [`ProstCodec`] for `prost` generated types with the `protobuf`
+//! feature.
+//!
+//! The status of a call is read from the trailers at the end of the response body, or from the
+//! headers of a trailers-only response. A status other than `OK` is returned as a
+//! [`GrpcError::Status`]. For cross-origin calls, the server must expose the `grpc-status` and
+//! `grpc-message` headers.
+//!
+//! Compressed messages are not supported.
+//!
+//! # Example
+//!
+//! ```rust
+//! use gloo_net::grpc::{GrpcClient, MessageCodec};
+//! use gloo_net::Error;
+//!
+//! /// Passes messages through as they are.
+//! struct Raw;
+//!
+//! impl MessageCodec for Raw {
+//!     type Request = Vec<u8>;
+//!     type Response = Vec<u8>;
+//!
+//!     fn encode(&self, request: &Vec<u8>) -> Result<Vec<u8>, Error> {
+//!         Ok(request.clone())
+//!     }
+//!
+//!     fn decode(&self, bytes: &[u8]) -> Result<Vec<u8>, Error> {
+//!         Ok(bytes.to_vec())
+//!     }
+//! }
+//!
+//! # async fn no_run() {
+//! let client = GrpcClient::new("https://api.example.com").metadata("authorization", "Bearer 123");
+//! let reply = client
+//!     .unary("/helloworld.Greeter/SayHello", Raw, &b"\x0a\x05world".to_vec())
+//!     .await
+//!     .unwrap();
+//! # }
+//! ```
+use crate::http::body::BodyStream;
+use crate::http::Request;
+use crate::Error;
+use futures_core::{ready, Stream};
+use js_sys::Uint8Array;
+use std::fmt;
+use std::future::poll_fn;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use thiserror::Error as ThisError;
+
+mod frame;
+mod status;
+
+use frame::{Frame, FrameDecoder};
+pub use status::{Code, Status};
+
+/// The error of a gRPC call.
+#[derive(Debug, ThisError)]
+#[non_exhaustive]
+pub enum GrpcError {
+    /// The call ended with a status other than `OK`, or the HTTP status of the response was not
+    /// 200.
+    #[error("{0}")]
+    Status(Status),
+    /// The request could not be sent, or a message could not be encoded or decoded.
+    #[error("{0}")]
+    Request(#[from] Error),
+    /// The response is not valid gRPC-web.
+    #[error("gRPC-web protocol error: {0}")]
+    Protocol(String),
+}
+
+/// Encodes the request messages of a call and decodes its response messages.
+pub trait MessageCodec {
+    /// The type of the request messages.
+    type Request;
+    /// The type of the response messages.
+    type Response;
+
+    /// The message format, appended to the `application/grpc-web+` content type.
+    fn format(&self) -> &str {
+        "proto"
+    }
+
+    /// Encode a request message.
+    fn encode(&self, request: &Self::Request) -> Result<Vec<u8>, Error>;
+
+    /// Decode a response message.
+    fn decode(&self, bytes: &[u8]) -> Result<Self::Response, Error>;
+}
+
+/// Encodes and decodes protobuf messages generated by `prost`.
+///
+/// ```rust
+/// use gloo_net::grpc::{GrpcClient, ProstCodec};
+///
+/// # async fn no_run() {
+/// // `String` stands in for the generated `HelloRequest` and `HelloReply`
+/// let client = GrpcClient::new("https://api.example.com");
+/// let reply = client
+///     .unary(
+///         "/helloworld.Greeter/SayHello",
+///         ProstCodec::<String, String>::new(),
+///         &"world".to_string(),
+///     )
+///     .await
+///     .unwrap();
+/// # }
+/// ```
+#[cfg(feature = "protobuf")]
+#[cfg_attr(docsrs, doc(cfg(feature = "protobuf")))]
+pub struct ProstCodec<Req, Resp> {
+    _marker: std::marker::PhantomData<fn(Req) -> Resp>,
+}
+
+#[cfg(feature = "protobuf")]
+impl<Req, Resp> ProstCodec<Req, Resp> {
+    /// Create a codec.
+    pub fn new() -> Self {
+        Self {
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "protobuf")]
+impl<Req, Resp> Default for ProstCodec<Req, Resp> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "protobuf")]
+impl<Req, Resp> Clone for ProstCodec<Req, Resp> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+#[cfg(feature = "protobuf")]
+impl<Req, Resp> Copy for ProstCodec<Req, Resp> {}
+
+#[cfg(feature = "protobuf")]
+impl<Req, Resp> fmt::Debug for ProstCodec<Req, Resp> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProstCodec").finish()
+    }
+}
+
+#[cfg(feature = "protobuf")]
+impl<Req, Resp> MessageCodec for ProstCodec<Req, Resp>
+where
+    Req: prost::Message,
+    Resp: prost::Message + Default,
+{
+    type Request = Req;
+    type Response = Resp;
+
+    fn encode(&self, request: &Req) -> Result<Vec<u8>, Error> {
+        Ok(request.encode_to_vec())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Resp, Error> {
+        Resp::decode(bytes).map_err(Error::from)
+    }
+}
+
+/// The longest timeout `grpc-timeout` can carry in milliseconds, which is 8 digits.
+const MAX_TIMEOUT_MILLIS: u128 = 99_999_999;
+
+/// A client of the gRPC services at a base URL.
+#[derive(Clone, Debug)]
+pub struct GrpcClient {
+    base_url: String,
+    metadata: Vec<(String, String)>,
+    timeout: Option<Duration>,
+}
+
+impl GrpcClient {
+    /// Create a client of the services at `base_url`, to which the method paths are appended.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        let mut base_url = base_url.into();
+        if base_url.ends_with('/') {
+            base_url.pop();
+        }
+        Self {
+            base_url,
+            metadata: Vec::new(),
+            timeout: None,
+        }
+    }
+
+    /// Adds metadata sent with every call, as a request header.
+    pub fn metadata(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.push((name.into(), value.into()));
+        self
+    }
+
+    /// Sets the deadline of every call, sent in the `grpc-timeout` header for the server to
+    /// enforce.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Make a unary call to a method, e.g. `/helloworld.Greeter/SayHello`.
+    pub async fn unary<C: MessageCodec>(
+        &self,
+        path: &str,
+        codec: C,
+        request: &C::Request,
+    ) -> Result<C::Response, GrpcError> {
+        let mut responses = self.server_streaming(path, codec, request).await?;
+
+        let response = match next(&mut responses).await {
+            Some(response) => response?,
+            None => return Err(GrpcError::Protocol("the response has no message".into())),
+        };
+        // the status comes after the message
+        match next(&mut responses).await {
+            None => Ok(response),
+            Some(Err(e)) => Err(e),
+            Some(Ok(_)) => Err(GrpcError::Protocol(
+                "the response of a unary call has several messages".into(),
+            )),
+        }
+    }
+
+    /// Make a server-streaming call to a method, returning the stream of response messages.
+    ///
+    /// Errors in the headers of the response, e.g. an unknown method, are returned right away.
+    /// Later ones, including the final status, are yielded by the stream.
+    pub async fn server_streaming<C: MessageCodec>(
+        &self,
+        path: &str,
+        codec: C,
+        request: &C::Request,
+    ) -> Result<Streaming<C>, GrpcError> {
+        let body = frame::encode(&codec.encode(request)?)?;
+        let content_type = format!("application/grpc-web+{}", codec.format());
+        let mut builder = Request::post(&format!("{}{}", self.base_url, path))
+            .header("Content-Type", &content_type)
+            .header("Accept", &content_type)
+            .header("X-Grpc-Web", "1");
+        if let Some(timeout) = self.timeout {
+            let millis = timeout.as_millis().min(MAX_TIMEOUT_MILLIS);
+            builder = builder.header("grpc-timeout", &format!("{millis}m"));
+        }
+        for (name, value) in &self.metadata {
+            builder = builder.header(name, value);
+        }
+        let response = builder
+            .body(Uint8Array::from(body.as_slice()))?
+            .send()
+            .await?;
+
+        let headers = response.headers();
+        let status = Status::from_metadata(
+            headers.get("grpc-status").as_deref(),
+            headers.get("grpc-message").as_deref(),
+        );
+        if response.status() != 200 {
+            return Err(GrpcError::Status(status.unwrap_or_else(|| {
+                Status::new(
+                    Code::from_http_status(response.status()),
+                    response.status_text(),
+                )
+            })));
+        }
+        let trailers_only = match status {
+            Some(status) if status.code != Code::Ok => return Err(GrpcError::Status(status)),
+            Some(_) => true,
+            None => false,
+        };
+
+        Ok(Streaming {
+            body: BodyStream::new(&response)?,
+            decoder: FrameDecoder::default(),
+            codec,
+            trailers_only,
+            done: false,
+        })
+    }
+}
+
+async fn next<S: Stream + Unpin>(stream: &mut S) -> Option<S::Item> {
+    poll_fn(|cx| Pin::new(&mut *stream).poll_next(cx)).await
+}
+
+/// The response messages of a server-streaming call, returned by
+/// [`GrpcClient::server_streaming`].
+///
+/// The stream ends after the last message if the call succeeded, or yields the
+/// [`GrpcError::Status`] it failed with and ends.
+pub struct Streaming<C> {
+    body: BodyStream,
+    decoder: FrameDecoder,
+    codec: C,
+    trailers_only: bool,
+    done: bool,
+}
+
+// the codec is never pinned
+impl<C> Unpin for Streaming<C> {}
+
+impl<C> fmt::Debug for Streaming<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Streaming")
+            .field("done", &self.done)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<C: MessageCodec> Stream for Streaming<C> {
+    type Item = Result<C::Response, GrpcError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            if this.done {
+                return Poll::Ready(None);
+            }
+
+            match this.decoder.next_frame() {
+                Ok(Some(Frame::Message(bytes))) => {
+                    return Poll::Ready(Some(this.codec.decode(&bytes).map_err(GrpcError::from)))
+                }
+                Ok(Some(Frame::Trailers(trailers))) => {
+                    this.done = true;
+                    let get = |name: &str| {
+                        trailers
+                            .iter()
+                            .find(|(key, _)| key == name)
+                            .map(|(_, value)| value.as_str())
+                    };
+                    return match Status::from_metadata(get("grpc-status"), get("grpc-message")) {
+                        Some(status) if status.code == Code::Ok => Poll::Ready(None),
+                        Some(status) => Poll::Ready(Some(Err(GrpcError::Status(status)))),
+                        None => Poll::Ready(Some(Err(GrpcError::Protocol(
+                            "the trailers have no grpc-status".into(),
+                        )))),
+                    };
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    this.done = true;
+                    return Poll::Ready(Some(Err(e)));
+                }
+            }
+
+            match ready!(Pin::new(&mut this.body).poll_next(cx)) {
+                Some(Ok(chunk)) => this.decoder.push(&chunk),
+                Some(Err(e)) => {
+                    this.done = true;
+                    return Poll::Ready(Some(Err(e.into())));
+                }
+                None => {
+                    this.done = true;
+                    // a trailers-only response has its status in the headers
+                    if !(this.trailers_only && this.decoder.is_empty()) {
+                        return Poll::Ready(Some(Err(GrpcError::Protocol(
+                            "the response ended without trailers".into(),
+                        ))));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::http::mock::MockTransport;
+    use crate::http::{Method, Response};
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    struct Raw;
+
+    impl MessageCodec for Raw {
+        type Request = Vec<u8>;
+        type Response = Vec<u8>;
+
+        fn encode(&self, request: &Vec<u8>) -> Result<Vec<u8>, Error> {
+            Ok(request.clone())
+        }
+
+        fn decode(&self, bytes: &[u8]) -> Result<Vec<u8>, Error> {
+            Ok(bytes.to_vec())
+        }
+    }
+
+    fn trailers(status: &str) -> Vec<u8> {
+        let trailers = format!("grpc-status:{status}\r\ngrpc-message:bad%20input\r\n");
+        let mut frame = vec![0x80];
+        frame.extend_from_slice(&(trailers.len() as u32).to_be_bytes());
+        frame.extend_from_slice(trailers.as_bytes());
+        frame
+    }
+
+    #[wasm_bindgen_test]
+    async fn unary_and_streaming_calls() {
+        let mock = MockTransport::new();
+        mock.on(Method::POST, "/test.Echo/Unary")
+            .header("X-Grpc-Web", "1")
+            .respond_with(|request| {
+                let mut body = frame::encode(&request.body[5..]).unwrap();
+                body.extend(trailers("0"));
+                Response::builder()
+                    .status(200)
+                    .body(Some(body.as_mut_slice()))
+            });
+        mock.on(Method::POST, "/test.Echo/Stream")
+            .respond_with(|_request| {
+                let mut body = frame::encode(b"1").unwrap();
+                body.extend(frame::encode(b"2").unwrap());
+                body.extend(trailers("3"));
+                Response::builder()
+                    .status(200)
+                    .body(Some(body.as_mut_slice()))
+            });
+        mock.on(Method::POST, "/test.Echo/Missing")
+            .respond_with(|_request| {
+                Response::builder()
+                    .status(200)
+                    .header("grpc-status", "12")
+                    .body(None::<&str>)
+            });
+        let _guard = mock.install();
+
+        let client = GrpcClient::new("/").timeout(Duration::from_secs(1));
+        let reply = client
+            .unary("/test.Echo/Unary", Raw, &b"hello".to_vec())
+            .await
+            .unwrap();
+        assert_eq!(reply, b"hello");
+
+        let mut responses = client
+            .server_streaming("/test.Echo/Stream", Raw, &Vec::new())
+            .await
+            .unwrap();
+        assert_eq!(next(&mut responses).await.unwrap().unwrap(), b"1");
+        assert_eq!(next(&mut responses).await.unwrap().unwrap(), b"2");
+        match next(&mut responses).await {
+            Some(Err(GrpcError::Status(status))) => {
+                assert_eq!(status, Status::new(Code::InvalidArgument, "bad input"))
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+        assert!(next(&mut responses).await.is_none());
+
+        match client.unary("/test.Echo/Missing", Raw, &Vec::new()).await {
+            Err(GrpcError::Status(status)) => assert_eq!(status.code, Code::Unimplemented),
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "protobuf"))]
+mod prost_tests {
+    use super::*;
+
+    #[test]
+    fn prost_messages_round_trip_through_frames() {
+        let codec = ProstCodec::<String, String>::new();
+        let body = frame::encode(&codec.encode(&"hello".to_string()).unwrap()).unwrap();
+
+        let mut decoder = FrameDecoder::default();
+        decoder.push(&body);
+        match decoder.next_frame().unwrap() {
+            Some(Frame::Message(bytes)) => assert_eq!(codec.decode(&bytes).unwrap(), "hello"),
+            other => panic!("unexpected {other:?}"),
+        }
+        assert!(decoder.is_empty());
+    }
+}
@@ -0,0 +1,167 @@
+use std::fmt;
+
+/// A gRPC status code.
+///
+/// See [the gRPC documentation](https://grpc.github.io/grpc/core/md_doc_statuscodes.html) for
+/// their meaning.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Code {
+    /// Not an error.
+    Ok,
+    /// The call was cancelled.
+    Cancelled,
+    /// An unknown error.
+    Unknown,
+    /// The client specified an invalid argument.
+    InvalidArgument,
+    /// The deadline expired before the call could complete.
+    DeadlineExceeded,
+    /// A requested entity was not found.
+    NotFound,
+    /// The entity the client attempted to create already exists.
+    AlreadyExists,
+    /// The caller does not have permission to execute the call.
+    PermissionDenied,
+    /// A resource has been exhausted, e.g. a quota.
+    ResourceExhausted,
+    /// The system is not in a state required for the call.
+    FailedPrecondition,
+    /// The call was aborted, e.g. because of a concurrency issue.
+    Aborted,
+    /// The call was attempted past the valid range.
+    OutOfRange,
+    /// The call is not implemented or supported by the server.
+    Unimplemented,
+    /// An internal error.
+    Internal,
+    /// The service is currently unavailable. Retrying may help.
+    Unavailable,
+    /// Unrecoverable data loss or corruption.
+    DataLoss,
+    /// The request does not have valid authentication credentials.
+    Unauthenticated,
+}
+
+impl Code {
+    /// The code of a `grpc-status` value, [`Code::Unknown`] if it is not valid.
+    pub fn from_i32(code: i32) -> Self {
+        match code {
+            0 => Code::Ok,
+            1 => Code::Cancelled,
+            2 => Code::Unknown,
+            3 => Code::InvalidArgument,
+            4 => Code::DeadlineExceeded,
+            5 => Code::NotFound,
+            6 => Code::AlreadyExists,
+            7 => Code::PermissionDenied,
+            8 => Code::ResourceExhausted,
+            9 => Code::FailedPrecondition,
+            10 => Code::Aborted,
+            11 => Code::OutOfRange,
+            12 => Code::Unimplemented,
+            13 => Code::Internal,
+            14 => Code::Unavailable,
+            15 => Code::DataLoss,
+            16 => Code::Unauthenticated,
+            _ => Code::Unknown,
+        }
+    }
+
+    /// The code of a response whose HTTP status is not 200, as gRPC maps them.
+    pub fn from_http_status(status: u16) -> Self {
+        match status {
+            400 => Code::Internal,
+            401 => Code::Unauthenticated,
+            403 => Code::PermissionDenied,
+            404 => Code::Unimplemented,
+            429 | 502 | 503 | 504 => Code::Unavailable,
+            _ => Code::Unknown,
+        }
+    }
+}
+
+/// The status of a failed gRPC call, from the `grpc-status` and `grpc-message` of the response.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Status {
+    /// The status code.
+    pub code: Code,
+    /// The error message, percent-decoded.
+    pub message: String,
+}
+
+impl Status {
+    /// Create a status.
+    pub fn new(code: Code, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    /// Reads the status from the `grpc-status` and `grpc-message` values of a response, if it
+    /// has one.
+    pub(crate) fn from_metadata(status: Option<&str>, message: Option<&str>) -> Option<Self> {
+        let code = status?.trim().parse().map_or(Code::Unknown, Code::from_i32);
+        Some(Self::new(code, percent_decode(message.unwrap_or_default())))
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "gRPC status {:?}", self.code)?;
+        if !self.message.is_empty() {
+            write!(f, ": {}", self.message)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Status {}
+
+/// `grpc-message` is percent-encoded UTF-8. Invalid escapes are kept as they are.
+fn percent_decode(message: &str) -> String {
+    let bytes = message.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_from_metadata() {
+        assert_eq!(Status::from_metadata(None, Some("ignored")), None);
+        assert_eq!(
+            Status::from_metadata(Some("5"), Some("no%20such%20user%3A %E2%9C%93 %zz")),
+            Some(Status::new(Code::NotFound, "no such user: ✓ %zz"))
+        );
+        assert_eq!(
+            Status::from_metadata(Some("99"), None),
+            Some(Status::new(Code::Unknown, ""))
+        );
+        assert_eq!(Code::from_http_status(503), Code::Unavailable);
+        assert_eq!(
+            Status::new(Code::Internal, "boom").to_string(),
+            "gRPC status Internal: boom"
+        );
+    }
+}
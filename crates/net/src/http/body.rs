@@ -0,0 +1,81 @@
+use crate::http::Response;
+use crate::{js_to_error, Error};
+use futures_core::{ready, Stream};
+use js_sys::{Reflect, Uint8Array};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::ReadableStreamDefaultReader;
+
+/// The chunks of a response body, read one at a time from its `ReadableStream`.
+///
+/// Dropping the stream before the end of the body cancels the underlying `ReadableStream`.
+pub(crate) struct BodyStream {
+    reader: Option<ReadableStreamDefaultReader>,
+    pending: Option<JsFuture>,
+}
+
+impl BodyStream {
+    /// Starts reading the body of the response.
+    ///
+    /// Fails if the body has already been read, or is being read, since the browser would throw.
+    pub(crate) fn new(response: &Response) -> Result<Self, Error> {
+        let body = response.body();
+        if response.body_used() || body.as_ref().map_or(false, |body| body.locked()) {
+            return Err(Error::GlooError(
+                "the response body has already been used".to_string(),
+            ));
+        }
+        Ok(Self {
+            reader: body.map(|body| body.get_reader().unchecked_into()),
+            pending: None,
+        })
+    }
+}
+
+impl Stream for BodyStream {
+    type Item = Result<Vec<u8>, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let reader = match &this.reader {
+            Some(reader) => reader,
+            None => return Poll::Ready(None),
+        };
+
+        let pending = this
+            .pending
+            .get_or_insert_with(|| JsFuture::from(reader.read()));
+        let result = ready!(Pin::new(pending).poll(cx));
+        this.pending = None;
+
+        let chunk = match result {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                this.reader = None;
+                return Poll::Ready(Some(Err(js_to_error(e))));
+            }
+        };
+
+        let done = Reflect::get(&chunk, &JsValue::from_str("done"))
+            .map(|done| done.is_truthy())
+            .unwrap_or(true);
+        if done {
+            this.reader = None;
+            return Poll::Ready(None);
+        }
+
+        let value = Reflect::get(&chunk, &JsValue::from_str("value")).map_err(js_to_error)?;
+        Poll::Ready(Some(Ok(Uint8Array::new(&value).to_vec())))
+    }
+}
+
+impl Drop for BodyStream {
+    fn drop(&mut self) {
+        if let Some(reader) = self.reader.take() {
+            let _ = reader.cancel();
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use crate::http::typed_headers::typed_header_accessors;
+use crate::http::Headers;
+use std::iter::FromIterator;
+
+/// A pure Rust collection of HTTP headers.
+///
+/// Unlike [`Headers`], a `HeaderMap` does not need a JavaScript environment, so it can be built
+/// and inspected anywhere, e.g. in unit tests. Like the `fetch` API, header names are
+/// case-insensitive and stored in lowercase, and a name may have several values.
+///
+/// # Example
+///
+/// ```
+/// use gloo_net::http::HeaderMap;
+///
+/// let mut headers: HeaderMap = [("Content-Type", "application/json")].into_iter().collect();
+/// headers.append("Accept", "text/html");
+/// headers.append("accept", "application/json");
+///
+/// assert_eq!(headers.get("content-type"), Some("application/json"));
+/// assert_eq!(headers.get_all("Accept").collect::<Vec<_>>(), ["text/html", "application/json"]);
+/// assert_eq!(headers.content_type().unwrap().essence_str(), "application/json");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct HeaderMap {
+    entries: Vec<(String, String)>,
+}
+
+impl HeaderMap {
+    /// Create a new empty header map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a value to a header, keeping any existing values.
+    pub fn append(&mut self, name: &str, value: &str) {
+        self.entries
+            .push((name.to_ascii_lowercase(), value.to_string()));
+    }
+
+    /// Overwrites all values of a header with the given value.
+    pub fn set(&mut self, name: &str, value: &str) {
+        self.delete(name);
+        self.append(name, value);
+    }
+
+    /// Deletes all values of a header.
+    pub fn delete(&mut self, name: &str) {
+        self.entries
+            .retain(|(key, _)| !key.eq_ignore_ascii_case(name));
+    }
+
+    /// Gets the first value of a header if it is present.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Iterate over all values of a header.
+    pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.entries
+            .iter()
+            .filter(move |(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Whether a header with the given name exists.
+    pub fn has(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// The number of `(name, value)` pairs.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether there are no headers.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterate over (header name, header value) pairs, in insertion order.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    typed_header_accessors!();
+}
+
+impl From<&Headers> for HeaderMap {
+    fn from(headers: &Headers) -> Self {
+        headers.entries().collect()
+    }
+}
+
+impl From<&HeaderMap> for Headers {
+    fn from(map: &HeaderMap) -> Self {
+        map.entries().collect()
+    }
+}
+
+impl From<HeaderMap> for Headers {
+    fn from(map: HeaderMap) -> Self {
+        Headers::from(&map)
+    }
+}
+
+impl<K: AsRef<str>, V: AsRef<str>> FromIterator<(K, V)> for HeaderMap {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut map = HeaderMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: AsRef<str>, V: AsRef<str>> Extend<(K, V)> for HeaderMap {
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (name, value) in iter {
+            self.append(name.as_ref(), value.as_ref());
+        }
+    }
+}
+
+impl IntoIterator for HeaderMap {
+    type Item = (String, String);
+    type IntoIter = std::vec::IntoIter<(String, String)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn case_insensitive_multimap() {
+        let mut headers = HeaderMap::new();
+        headers.append("X-Custom", "1");
+        headers.append("x-custom", "2");
+        headers.append("ETag", "\"abc\"");
+        assert_eq!(headers.get_all("X-CUSTOM").collect::<Vec<_>>(), ["1", "2"]);
+        assert_eq!(headers.etag().unwrap().tag, "abc");
+
+        headers.set("X-Custom", "3");
+        assert_eq!(headers.get_all("x-custom").collect::<Vec<_>>(), ["3"]);
+
+        headers.delete("x-CUSTOM");
+        assert!(!headers.has("x-custom"));
+        assert_eq!(headers.len(), 1);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn serde_round_trip() {
+        let headers: HeaderMap = [("Accept", "a"), ("accept", "b")].into_iter().collect();
+        let json = serde_json::to_string(&headers).unwrap();
+        assert_eq!(json, r#"[["accept","a"],["accept","b"]]"#);
+        assert_eq!(serde_json::from_str::<HeaderMap>(&json).unwrap(), headers);
+    }
+}
@@ -1,6 +1,8 @@
+use crate::http::typed_headers::typed_header_accessors;
 use gloo_utils::iter::UncheckedIter;
 use js_sys::{Array, Map};
 use std::fmt;
+use std::iter::FromIterator;
 use wasm_bindgen::{JsCast, UnwrapThrowExt};
 
 // I experimented with using `js_sys::Object` for the headers, since this object is marked
@@ -91,6 +93,24 @@ impl Headers {
         let fake_map: &Map = self.raw.unchecked_ref();
         UncheckedIter::from(fake_map.values()).map(|v| v.as_string().unwrap_throw())
     }
+
+    typed_header_accessors!();
+}
+
+impl<K: AsRef<str>, V: AsRef<str>> FromIterator<(K, V)> for Headers {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut headers = Headers::new();
+        headers.extend(iter);
+        headers
+    }
+}
+
+impl<K: AsRef<str>, V: AsRef<str>> Extend<(K, V)> for Headers {
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (name, value) in iter {
+            self.append(name.as_ref(), value.as_ref());
+        }
+    }
 }
 
 impl fmt::Debug for Headers {
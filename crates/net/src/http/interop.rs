@@ -0,0 +1,124 @@
+//! Conversions between the types of this module and the types of the [`http`] crate.
+//!
+//! [`Method`](crate::http::Method) is a re-export of [`http::Method`], so it needs no conversion.
+//!
+//! Reading the body of a [`Request`] or [`Response`] is asynchronous, so the conversions into the
+//! [`http`] types are the async [`Request::into_http`] and [`Response::into_http`] methods rather
+//! than `From` implementations.
+
+use crate::http::{Headers, Request, RequestBuilder, Response, StatusCode};
+use crate::Error;
+use js_sys::Uint8Array;
+use std::convert::TryFrom;
+
+impl From<&http::HeaderMap> for Headers {
+    fn from(map: &http::HeaderMap) -> Self {
+        let headers = Headers::new();
+        for (name, value) in map {
+            headers.append(name.as_str(), &String::from_utf8_lossy(value.as_bytes()));
+        }
+        headers
+    }
+}
+
+impl TryFrom<&Headers> for http::HeaderMap {
+    type Error = Error;
+
+    fn try_from(headers: &Headers) -> Result<Self, Self::Error> {
+        let mut map = http::HeaderMap::new();
+        for (name, value) in headers.entries() {
+            map.append(
+                http::HeaderName::try_from(name).map_err(http::Error::from)?,
+                http::HeaderValue::try_from(value).map_err(http::Error::from)?,
+            );
+        }
+        Ok(map)
+    }
+}
+
+impl From<http::StatusCode> for StatusCode {
+    fn from(code: http::StatusCode) -> Self {
+        StatusCode::from_u16(code.as_u16())
+    }
+}
+
+impl TryFrom<StatusCode> for http::StatusCode {
+    type Error = Error;
+
+    fn try_from(code: StatusCode) -> Result<Self, Self::Error> {
+        http::StatusCode::from_u16(code.as_u16()).map_err(|e| Error::from(http::Error::from(e)))
+    }
+}
+
+impl TryFrom<http::Request<Vec<u8>>> for Request {
+    type Error = Error;
+
+    fn try_from(request: http::Request<Vec<u8>>) -> Result<Self, Self::Error> {
+        let (parts, body) = request.into_parts();
+        let builder = RequestBuilder::new(&parts.uri.to_string())
+            .method(parts.method)
+            .headers(Headers::from(&parts.headers));
+
+        // `fetch` rejects `GET` and `HEAD` requests with a body, even an empty one.
+        if body.is_empty() {
+            builder.build()
+        } else {
+            builder.body(Uint8Array::from(body.as_slice()))
+        }
+    }
+}
+
+impl TryFrom<http::Response<Vec<u8>>> for Response {
+    type Error = Error;
+
+    fn try_from(response: http::Response<Vec<u8>>) -> Result<Self, Self::Error> {
+        let (parts, mut body) = response.into_parts();
+        let builder = Response::builder()
+            .status(parts.status.as_u16())
+            .status_text(parts.status.canonical_reason().unwrap_or_default())
+            .headers(Headers::from(&parts.headers));
+
+        // Null body statuses, like `204`, cannot be constructed with a body.
+        if body.is_empty() {
+            builder.body(None as Option<&str>)
+        } else {
+            builder.body(Some(body.as_mut_slice()))
+        }
+    }
+}
+
+impl Request {
+    /// Converts this request into an [`http::Request`], reading the body to completion.
+    #[cfg_attr(docsrs, doc(cfg(feature = "http-interop")))]
+    pub async fn into_http(self) -> Result<http::Request<Vec<u8>>, Error> {
+        let mut builder = http::Request::builder()
+            .method(self.method())
+            .uri(self.url());
+        for (name, value) in self.headers().entries() {
+            builder = builder.header(name, value);
+        }
+
+        let body = match self.body() {
+            Some(_) => self.binary().await?,
+            None => Vec::new(),
+        };
+        builder.body(body).map_err(Error::from)
+    }
+}
+
+impl Response {
+    /// Converts this response into an [`http::Response`], reading the body to completion.
+    #[cfg_attr(docsrs, doc(cfg(feature = "http-interop")))]
+    pub async fn into_http(self) -> Result<http::Response<Vec<u8>>, Error> {
+        let mut builder = http::Response::builder().status(self.status());
+        for (name, value) in self.headers().entries() {
+            builder = builder.header(name, value);
+        }
+
+        let body = match self.body() {
+            Some(_) => self.binary().await?,
+            None => Vec::new(),
+        };
+        builder.body(body).map_err(Error::from)
+    }
+}
@@ -0,0 +1,109 @@
+use crate::http::body::BodyStream;
+use crate::Error;
+use futures_core::{ready, Stream};
+use serde::de::DeserializeOwned;
+use std::fmt;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// A stream of values decoded from a newline-delimited JSON response body.
+///
+/// Returned by [`Response::json_lines`](crate::http::Response::json_lines). Each non-blank line
+/// of the body is decoded as soon as it has been received, without buffering the whole body. A
+/// line that fails to decode yields an [`Error::SerdeError`] and the stream continues with the
+/// next line.
+pub struct JsonLines<T> {
+    body: BodyStream,
+    buffer: Vec<u8>,
+    done: bool,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> JsonLines<T> {
+    pub(crate) fn new(body: BodyStream) -> Self {
+        Self {
+            body,
+            buffer: Vec::new(),
+            done: false,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: DeserializeOwned> Stream for JsonLines<T> {
+    type Item = Result<T, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            if let Some(line) = next_line(&mut this.buffer, this.done) {
+                return Poll::Ready(Some(serde_json::from_slice(&line).map_err(Error::from)));
+            }
+            if this.done {
+                return Poll::Ready(None);
+            }
+
+            match ready!(Pin::new(&mut this.body).poll_next(cx)) {
+                Some(Ok(chunk)) => this.buffer.extend_from_slice(&chunk),
+                Some(Err(e)) => {
+                    this.done = true;
+                    this.buffer.clear();
+                    return Poll::Ready(Some(Err(e)));
+                }
+                None => this.done = true,
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for JsonLines<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonLines")
+            .field("buffered", &self.buffer.len())
+            .field("done", &self.done)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Removes the next non-blank line from `buffer`, without its line terminator.
+///
+/// An unterminated last line is only returned once the body has ended.
+fn next_line(buffer: &mut Vec<u8>, ended: bool) -> Option<Vec<u8>> {
+    loop {
+        let mut line = match buffer.iter().position(|&b| b == b'\n') {
+            Some(index) => {
+                let mut line: Vec<u8> = buffer.drain(..=index).collect();
+                line.pop();
+                line
+            }
+            None if ended && !buffer.is_empty() => std::mem::take(buffer),
+            None => return None,
+        };
+
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        if !line.iter().all(u8::is_ascii_whitespace) {
+            return Some(line);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_lines_across_chunks() {
+        let mut buffer = b"{\"a\":1}\r\n\n{\"a\"".to_vec();
+        assert_eq!(next_line(&mut buffer, false), Some(b"{\"a\":1}".to_vec()));
+        assert_eq!(next_line(&mut buffer, false), None);
+
+        buffer.extend_from_slice(b":2}\n  \n{\"a\":3}");
+        assert_eq!(next_line(&mut buffer, false), Some(b"{\"a\":2}".to_vec()));
+        assert_eq!(next_line(&mut buffer, false), None);
+        assert_eq!(next_line(&mut buffer, true), Some(b"{\"a\":3}".to_vec()));
+        assert_eq!(next_line(&mut buffer, true), None);
+    }
+}
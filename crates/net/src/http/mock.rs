@@ -0,0 +1,285 @@
+//! An in-process [`Transport`] for testing code that sends HTTP requests.
+//!
+//! A [`MockTransport`] answers requests from a list of routes instead of the network, and records
+//! every request it receives so tests can make assertions about them.
+//!
+//! # Example
+//!
+//! ```
+//! # async fn no_run() {
+//! use gloo_net::http::mock::MockTransport;
+//! use gloo_net::http::{Method, Request, Response};
+//!
+//! let mock = MockTransport::new();
+//! mock.on(Method::GET, "/users/*")
+//!     .header("Accept", "application/json")
+//!     .respond_with(|_request| Response::builder().status(200).body(Some("[]")));
+//! let _guard = mock.install();
+//!
+//! let resp = Request::get("/users/42")
+//!     .header("Accept", "application/json")
+//!     .send()
+//!     .await
+//!     .unwrap();
+//! assert_eq!(resp.status(), 200);
+//! assert!(mock.requests()[0].url.ends_with("/users/42"));
+//! # }
+//! ```
+
+use crate::http::{
+    set_transport, Method, Request, Response, Transport, TransportFuture, TransportGuard,
+};
+use crate::Error;
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+type Responder = Rc<dyn Fn(&RecordedRequest) -> Result<Response, Error>>;
+
+/// A [`Transport`] that answers requests from a list of mock routes.
+///
+/// Cloning a `MockTransport` returns a handle to the same routes and recorded requests.
+#[derive(Clone, Default)]
+pub struct MockTransport {
+    inner: Rc<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    routes: RefCell<Vec<Route>>,
+    requests: RefCell<Vec<RecordedRequest>>,
+}
+
+struct Route {
+    method: Method,
+    url: String,
+    headers: Vec<(String, String)>,
+    responder: Responder,
+}
+
+impl MockTransport {
+    /// Create a new mock transport without any routes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start defining a route for requests with the given method and URL pattern.
+    ///
+    /// A `*` in the pattern matches any sequence of characters. A pattern starting with `/` is
+    /// matched against the path and query of the request URL, any other pattern against the
+    /// whole URL.
+    ///
+    /// Routes are tried in the order they were added, and the first matching route answers the
+    /// request. Requests that match no route fail with [`Error::GlooError`].
+    pub fn on(&self, method: Method, url: &str) -> MockRoute {
+        MockRoute {
+            mock: self.clone(),
+            method,
+            url: url.to_string(),
+            headers: Vec::new(),
+        }
+    }
+
+    /// Install this transport for the current thread, until the returned guard is dropped.
+    pub fn install(&self) -> TransportGuard {
+        set_transport(self.clone())
+    }
+
+    /// The requests received so far, in the order they were sent.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.inner.requests.borrow().clone()
+    }
+
+    /// Forget the requests received so far.
+    pub fn clear_requests(&self) {
+        self.inner.requests.borrow_mut().clear();
+    }
+}
+
+impl Transport for MockTransport {
+    fn send(&self, request: Request) -> TransportFuture {
+        let inner = Rc::clone(&self.inner);
+        Box::pin(async move {
+            let body = match request.body() {
+                Some(_) => request.binary().await?,
+                None => Vec::new(),
+            };
+            let recorded = RecordedRequest {
+                method: request.method(),
+                url: request.url(),
+                headers: request.headers().entries().collect(),
+                body,
+            };
+
+            let responder = inner
+                .routes
+                .borrow()
+                .iter()
+                .find(|route| route.matches(&recorded))
+                .map(|route| Rc::clone(&route.responder));
+            inner.requests.borrow_mut().push(recorded.clone());
+
+            match responder {
+                Some(responder) => responder(&recorded),
+                None => Err(Error::GlooError(format!(
+                    "no mock route matches {} {}",
+                    recorded.method, recorded.url
+                ))),
+            }
+        })
+    }
+}
+
+impl fmt::Debug for MockTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockTransport")
+            .field("routes", &self.inner.routes.borrow().len())
+            .field("requests", &self.inner.requests.borrow())
+            .finish()
+    }
+}
+
+impl Route {
+    fn matches(&self, request: &RecordedRequest) -> bool {
+        let url = if self.url.starts_with('/') {
+            path_and_query(&request.url)
+        } else {
+            &request.url
+        };
+
+        self.method == request.method
+            && glob_match(&self.url, url)
+            && self
+                .headers
+                .iter()
+                .all(|(name, value)| request.header(name) == Some(value.as_str()))
+    }
+}
+
+/// A route of a [`MockTransport`] being defined. Created with [`MockTransport::on`].
+///
+/// The route is added to the transport by [`respond_with`](Self::respond_with).
+pub struct MockRoute {
+    mock: MockTransport,
+    method: Method,
+    url: String,
+    headers: Vec<(String, String)>,
+}
+
+impl MockRoute {
+    /// Only match requests that have a header with the given value.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Answer matching requests with the result of `responder`.
+    ///
+    /// The responder is called once per request, so it can build a fresh [`Response`] each time
+    /// with [`Response::builder`], or return an error to simulate a network failure.
+    pub fn respond_with<F>(self, responder: F)
+    where
+        F: Fn(&RecordedRequest) -> Result<Response, Error> + 'static,
+    {
+        self.mock.inner.routes.borrow_mut().push(Route {
+            method: self.method,
+            url: self.url,
+            headers: self.headers,
+            responder: Rc::new(responder),
+        });
+    }
+}
+
+impl fmt::Debug for MockRoute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockRoute")
+            .field("method", &self.method)
+            .field("url", &self.url)
+            .field("headers", &self.headers)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A request received by a [`MockTransport`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecordedRequest {
+    /// The request method.
+    pub method: Method,
+    /// The absolute URL of the request.
+    pub url: String,
+    /// The request headers, as `(name, value)` pairs.
+    pub headers: Vec<(String, String)>,
+    /// The request body. Empty if the request had no body.
+    pub body: Vec<u8>,
+}
+
+impl RecordedRequest {
+    /// Gets the value of a header, comparing names case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The request body as text, with invalid UTF-8 replaced.
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+fn path_and_query(url: &str) -> &str {
+    let after_scheme = url.find("://").map_or(0, |index| index + 3);
+    match url[after_scheme..].find('/') {
+        Some(index) => &url[after_scheme + index..],
+        None => "/",
+    }
+}
+
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    // `split` always yields at least one item.
+    let first = parts.next().unwrap_or_default();
+    let mut rest = match text.strip_prefix(first) {
+        Some(rest) => rest,
+        None => return false,
+    };
+
+    let parts: Vec<&str> = parts.collect();
+    let (last, middle) = match parts.split_last() {
+        Some(split) => split,
+        None => return rest.is_empty(),
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_patterns() {
+        assert!(glob_match("/users", "/users"));
+        assert!(!glob_match("/users", "/users/42"));
+        assert!(glob_match("/users/*", "/users/42"));
+        assert!(glob_match("/users/*/posts", "/users/42/posts"));
+        assert!(!glob_match("/users/*/posts", "/users/42/comments"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("/a*a", "/a"));
+        assert!(glob_match("/a*a", "/aa"));
+        assert!(!glob_match("/ab*ab", "/ab"));
+    }
+
+    #[test]
+    fn url_path_and_query() {
+        assert_eq!(path_and_query("http://localhost:8080/a?b=c"), "/a?b=c");
+        assert_eq!(path_and_query("http://localhost"), "/");
+        assert_eq!(path_and_query("/relative"), "/relative");
+    }
+}
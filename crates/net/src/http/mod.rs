@@ -13,15 +13,42 @@
 //! # }
 //! ```
 
+#[cfg(any(feature = "json", feature = "eventsource", feature = "grpc"))]
+pub(crate) mod body;
+mod header_map;
 mod headers;
+#[cfg(feature = "http-interop")]
+mod interop;
+#[cfg(feature = "json")]
+mod json_lines;
+#[cfg(all(feature = "http", feature = "mock"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "http", feature = "mock"))))]
+pub mod mock;
+#[cfg(feature = "json")]
+mod problem;
 mod query;
 mod request;
 mod response;
+mod status;
+mod transport;
+mod typed_headers;
 
+pub use header_map::HeaderMap;
 pub use headers::Headers;
 #[doc(inline)]
 pub use http::Method;
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+pub use json_lines::JsonLines;
+#[doc(inline)]
+pub use mime::Mime;
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+pub use problem::{ProblemDetails, PROBLEM_JSON};
 pub use query::QueryParams;
 
 pub use request::{Request, RequestBuilder};
 pub use response::{IntoRawResponse, Response, ResponseBuilder};
+pub use status::StatusCode;
+pub use transport::{set_transport, Transport, TransportFuture, TransportGuard};
+pub use typed_headers::{Authorization, CacheControl, ETag};
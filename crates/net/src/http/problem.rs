@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The `Content-Type` of an [RFC 7807](https://www.rfc-editor.org/rfc/rfc7807) problem details
+/// response body.
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// A machine-readable description of an HTTP API error, as defined by
+/// [RFC 7807](https://www.rfc-editor.org/rfc/rfc7807).
+///
+/// [`Response::error_for_status`](crate::http::Response::error_for_status) decodes this from
+/// responses with the `application/problem+json` content type and returns it as
+/// [`Error::Problem`](crate::Error::Problem).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProblemDetails {
+    /// A URI reference that identifies the problem type. When absent, it is assumed to be
+    /// `about:blank`.
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub type_: Option<String>,
+    /// A short, human-readable summary of the problem type.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// The HTTP status code generated by the origin server for this occurrence of the problem.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    /// A human-readable explanation specific to this occurrence of the problem.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// A URI reference that identifies the specific occurrence of the problem.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    /// Any extension members of the problem type.
+    #[serde(flatten)]
+    pub extensions: serde_json::Map<String, serde_json::Value>,
+}
+
+impl fmt::Display for ProblemDetails {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.title, &self.detail) {
+            (Some(title), Some(detail)) => write!(f, "{title}: {detail}"),
+            (Some(message), None) | (None, Some(message)) => write!(f, "{message}"),
+            (None, None) => write!(f, "{}", self.type_.as_deref().unwrap_or("about:blank")),
+        }
+    }
+}
+
+/// Whether the given `Content-Type` header value denotes a problem details body.
+pub(crate) fn is_problem_json(content_type: &str) -> bool {
+    content_type
+        .split(';')
+        .next()
+        .map(|mime| mime.trim().eq_ignore_ascii_case(PROBLEM_JSON))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn problem_details_with_extensions() {
+        let problem: ProblemDetails = serde_json::from_str(
+            r#"{
+                "type": "https://example.com/probs/out-of-credit",
+                "title": "You do not have enough credit.",
+                "status": 403,
+                "detail": "Your current balance is 30, but that costs 50.",
+                "instance": "/account/12345/msgs/abc",
+                "balance": 30
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            problem.type_.as_deref(),
+            Some("https://example.com/probs/out-of-credit")
+        );
+        assert_eq!(problem.status, Some(403));
+        assert_eq!(problem.extensions["balance"], 30);
+        assert_eq!(
+            problem.to_string(),
+            "You do not have enough credit.: Your current balance is 30, but that costs 50."
+        );
+    }
+
+    #[test]
+    fn problem_json_content_type() {
+        assert!(is_problem_json("application/problem+json"));
+        assert!(is_problem_json("Application/Problem+JSON; charset=utf-8"));
+        assert!(!is_problem_json("application/json"));
+    }
+}
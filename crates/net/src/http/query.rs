@@ -3,6 +3,11 @@ use js_sys::{Array, Map};
 use std::fmt;
 use wasm_bindgen::{JsCast, UnwrapThrowExt};
 
+#[cfg(feature = "urlencoded")]
+use crate::Error;
+#[cfg(feature = "urlencoded")]
+use serde::{de::DeserializeOwned, Serialize};
+
 /// A sequence of URL query parameters, wrapping [`web_sys::UrlSearchParams`].
 pub struct QueryParams {
     raw: web_sys::UrlSearchParams,
@@ -72,6 +77,60 @@ impl QueryParams {
     }
 }
 
+#[cfg(feature = "urlencoded")]
+#[cfg_attr(docsrs, doc(cfg(feature = "urlencoded")))]
+impl QueryParams {
+    /// Create [`QueryParams`] by serializing `value` with `serde`.
+    ///
+    /// `value` must serialize to a struct, a map or a sequence of pairs. Sequence fields, such as
+    /// a `Vec`, are encoded as repeated keys: `{ "a": [1, 2] }` becomes `a=1&a=2`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn no_run() {
+    /// use gloo_net::http::QueryParams;
+    /// use serde::Serialize;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Search {
+    ///     q: String,
+    ///     tag: Vec<String>,
+    /// }
+    ///
+    /// let params = QueryParams::from_serde(&Search {
+    ///     q: "ferris".to_string(),
+    ///     tag: vec!["crab".to_string(), "rust".to_string()],
+    /// })
+    /// .unwrap();
+    /// assert_eq!(params.to_string(), "q=ferris&tag=crab&tag=rust");
+    /// # }
+    /// ```
+    pub fn from_serde<T: Serialize + ?Sized>(value: &T) -> Result<Self, Error> {
+        let params = Self::new();
+        for (name, value) in encode_pairs(value)? {
+            params.append(&name, &value);
+        }
+        Ok(params)
+    }
+
+    /// Deserialize the query parameters into `T` with `serde`.
+    ///
+    /// Repeated keys can be collected into sequence fields, such as a `Vec`.
+    pub fn to_serde<T: DeserializeOwned>(&self) -> Result<T, Error> {
+        serde_html_form::from_str(&self.to_string()).map_err(Error::from)
+    }
+}
+
+/// Serialize `value` into `(name, value)` pairs, with sequences as repeated keys.
+#[cfg(feature = "urlencoded")]
+pub(crate) fn encode_pairs<T: Serialize + ?Sized>(
+    value: &T,
+) -> Result<Vec<(String, String)>, Error> {
+    let encoded = serde_html_form::to_string(value)?;
+    serde_html_form::from_str(&encoded).map_err(Error::from)
+}
+
 /// The formatted query parameters ready to be used in a URL query string.
 ///
 /// # Examples
@@ -102,3 +161,35 @@ impl fmt::Debug for QueryParams {
         f.debug_list().entries(self.iter()).finish()
     }
 }
+
+#[cfg(all(test, feature = "urlencoded"))]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Search {
+        q: String,
+        tag: Vec<String>,
+    }
+
+    #[test]
+    fn encode_repeated_keys() {
+        let search = Search {
+            q: "a b&c".to_string(),
+            tag: vec!["x".to_string(), "y".to_string()],
+        };
+        assert_eq!(
+            encode_pairs(&search).unwrap(),
+            vec![
+                ("q".to_string(), "a b&c".to_string()),
+                ("tag".to_string(), "x".to_string()),
+                ("tag".to_string(), "y".to_string()),
+            ]
+        );
+        assert_eq!(
+            serde_html_form::from_str::<Search>("q=a+b%26c&tag=x&tag=y").unwrap(),
+            search
+        );
+    }
+}
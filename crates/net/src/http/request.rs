@@ -1,3 +1,6 @@
+#[cfg(feature = "urlencoded")]
+use crate::http::query::encode_pairs;
+use crate::http::transport::current_transport;
 use crate::http::{Headers, QueryParams, Response};
 use crate::{js_to_error, Error};
 use http::Method;
@@ -61,6 +64,21 @@ impl RequestBuilder {
         self
     }
 
+    /// A convenience method to set CBOR as request body
+    ///
+    /// # Note
+    ///
+    /// This method also sets the `Content-Type` and `Accept` headers to `application/cbor`
+    #[cfg(feature = "cbor")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cbor")))]
+    pub fn cbor<T: serde::Serialize + ?Sized>(self, value: &T) -> Result<Request, Error> {
+        let mut cbor = Vec::new();
+        ciborium::into_writer(value, &mut cbor)?;
+        self.header("Content-Type", "application/cbor")
+            .header("Accept", "application/cbor")
+            .body(Uint8Array::from(cbor.as_slice()))
+    }
+
     /// Controls what browsers do with credentials (cookies, HTTP authentication entries, and TLS
     /// client certificates).
     pub fn credentials(mut self, credentials: RequestCredentials) -> Self {
@@ -118,6 +136,39 @@ impl RequestBuilder {
         self
     }
 
+    /// Append query parameters to the url by serializing `params` with `serde`.
+    ///
+    /// `params` must serialize to a struct, a map or a sequence of pairs. Sequence fields, such as
+    /// a `Vec`, are appended as repeated keys. See [`QueryParams::from_serde`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn no_run() {
+    /// use gloo_net::http::Request;
+    /// use serde::Serialize;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Search<'a> {
+    ///     q: &'a str,
+    ///     page: u32,
+    /// }
+    ///
+    /// let r = Request::get("/search")
+    ///     .query_serde(&Search { q: "ferris", page: 2 })
+    ///     .unwrap();
+    /// // Result URL: /search?q=ferris&page=2
+    /// # }
+    /// ```
+    #[cfg(feature = "urlencoded")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "urlencoded")))]
+    pub fn query_serde<T: serde::Serialize + ?Sized>(self, params: &T) -> Result<Self, Error> {
+        for (name, value) in encode_pairs(params)? {
+            self.query.append(&name, &value);
+        }
+        Ok(self)
+    }
+
     /// The subresource integrity value of the request (e.g.,
     /// `sha256-BpfBw7ivV8q2jLiT13fxDYAe2tJllusRSZ273h2nFSE=`).
     pub fn integrity(mut self, integrity: &str) -> Self {
@@ -137,6 +188,21 @@ impl RequestBuilder {
         self.header("Content-Type", "application/json").body(json)
     }
 
+    /// A convenience method to set an `application/x-www-form-urlencoded` form as request body
+    ///
+    /// Sequence fields, such as a `Vec`, are encoded as repeated keys.
+    ///
+    /// # Note
+    ///
+    /// This method also sets the `Content-Type` header to `application/x-www-form-urlencoded`
+    #[cfg(feature = "urlencoded")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "urlencoded")))]
+    pub fn form<T: serde::Serialize + ?Sized>(self, value: &T) -> Result<Request, Error> {
+        let form = serde_html_form::to_string(value)?;
+        self.header("Content-Type", "application/x-www-form-urlencoded")
+            .body(form)
+    }
+
     /// The request method, e.g., GET, POST.
     pub fn method(mut self, method: Method) -> Self {
         self.options.method(method.as_ref());
@@ -149,6 +215,20 @@ impl RequestBuilder {
         self
     }
 
+    /// A convenience method to set MessagePack as request body
+    ///
+    /// # Note
+    ///
+    /// This method also sets the `Content-Type` and `Accept` headers to `application/msgpack`
+    #[cfg(feature = "msgpack")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "msgpack")))]
+    pub fn msgpack<T: serde::Serialize + ?Sized>(self, value: &T) -> Result<Request, Error> {
+        let msgpack = rmp_serde::to_vec_named(value)?;
+        self.header("Content-Type", "application/msgpack")
+            .header("Accept", "application/msgpack")
+            .body(Uint8Array::from(msgpack.as_slice()))
+    }
+
     /// Sets the observer callback.
     pub fn observe(mut self, observe: &ObserverCallback) -> Self {
         self.options.observe(observe);
@@ -329,7 +409,18 @@ impl Request {
     }
 
     /// Executes the request.
+    ///
+    /// The request is sent with the global `fetch` function, unless a different
+    /// [`Transport`](crate::http::Transport) was installed with
+    /// [`set_transport`](crate::http::set_transport).
     pub async fn send(self) -> Result<Response, Error> {
+        match current_transport() {
+            Some(transport) => transport.send(self).await,
+            None => self.fetch().await,
+        }
+    }
+
+    async fn fetch(self) -> Result<Response, Error> {
         let request = self.0;
         let promise = fetch_with_request(&request);
         let response = JsFuture::from(promise).await.map_err(js_to_error)?;
@@ -6,9 +6,14 @@ use wasm_bindgen::{JsCast, JsValue};
 use wasm_bindgen_futures::JsFuture;
 use web_sys::ResponseInit;
 
-use crate::http::Headers;
 #[cfg(feature = "json")]
-#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+use crate::http::body::BodyStream;
+#[cfg(feature = "json")]
+use crate::http::problem::{is_problem_json, ProblemDetails};
+#[cfg(feature = "json")]
+use crate::http::JsonLines;
+use crate::http::{Headers, StatusCode};
+#[cfg(any(feature = "json", feature = "cbor", feature = "msgpack"))]
 use serde::de::DeserializeOwned;
 
 /// The [`Request`]'s response
@@ -55,6 +60,12 @@ impl Response {
         self.0.status()
     }
 
+    /// The [HTTP status code](https://developer.mozilla.org/en-US/docs/Web/HTTP/Status) of the
+    /// response, as a [`StatusCode`].
+    pub fn status_code(&self) -> StatusCode {
+        StatusCode::from(self.0.status())
+    }
+
     /// Whether the [HTTP status code](https://developer.mozilla.org/en-US/docs/Web/HTTP/Status)
     /// was a success code (in the range `200 - 299`).
     pub fn ok(&self) -> bool {
@@ -71,6 +82,53 @@ impl Response {
         self.0.status_text()
     }
 
+    /// Turns a response that is not [`ok`](Self::ok) into an [`Error::Http`].
+    ///
+    /// The body is read as text and kept in the error for diagnostics. Successful responses are
+    /// returned unchanged, with their body unread.
+    ///
+    /// With the `json` feature, a body with the `application/problem+json` content type is
+    /// decoded into [`ProblemDetails`](crate::http::ProblemDetails) and returned as
+    /// [`Error::Problem`] instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use gloo_net::http::Request;
+    /// # async fn no_run() -> Result<(), gloo_net::Error> {
+    /// let resp = Request::get("/path").send().await?.error_for_status().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn error_for_status(self) -> Result<Self, Error> {
+        if self.ok() {
+            return Ok(self);
+        }
+
+        let status = self.status_code();
+        let body = self.text().await.unwrap_or_default();
+
+        #[cfg(feature = "json")]
+        if self
+            .headers()
+            .get("Content-Type")
+            .map_or(false, |content_type| is_problem_json(&content_type))
+        {
+            if let Ok(details) = serde_json::from_str::<ProblemDetails>(&body) {
+                return Err(Error::Problem {
+                    status,
+                    details: Box::new(details),
+                });
+            }
+        }
+
+        Err(Error::Http {
+            status,
+            status_text: self.status_text(),
+            body,
+        })
+    }
+
     /// Gets the headers.
     pub fn headers(&self) -> Headers {
         Headers::from_raw(self.0.headers())
@@ -102,6 +160,51 @@ impl Response {
         serde_json::from_str::<T>(&self.text().await?).map_err(Error::from)
     }
 
+    /// Reads the response as newline-delimited JSON (also known as JSON Lines or NDJSON).
+    ///
+    /// Each line of the body is decoded into a `T` as soon as it has been received, so this is
+    /// suitable for long-lived streaming responses. Blank lines are skipped.
+    ///
+    /// Returns an error if the body has already been used, e.g. by [`text`](Self::text) or a
+    /// previous call.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use gloo_net::http::Request;
+    /// # use futures::StreamExt;
+    /// # #[derive(serde::Deserialize)]
+    /// # struct Token { text: String }
+    /// # async fn no_run() {
+    /// let resp = Request::get("/stream").send().await.unwrap();
+    /// let mut tokens = resp.json_lines::<Token>().unwrap();
+    /// while let Some(token) = tokens.next().await {
+    ///     let token = token.unwrap();
+    /// }
+    /// # }
+    /// ```
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub fn json_lines<T: DeserializeOwned>(&self) -> Result<JsonLines<T>, Error> {
+        BodyStream::new(self).map(JsonLines::new)
+    }
+
+    /// Reads the response to completion, parsing it as CBOR.
+    #[cfg(feature = "cbor")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cbor")))]
+    pub async fn cbor<T: DeserializeOwned>(&self) -> Result<T, Error> {
+        let body = self.binary().await?;
+        ciborium::from_reader(body.as_slice()).map_err(Error::from)
+    }
+
+    /// Reads the response to completion, parsing it as MessagePack.
+    #[cfg(feature = "msgpack")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "msgpack")))]
+    pub async fn msgpack<T: DeserializeOwned>(&self) -> Result<T, Error> {
+        let body = self.binary().await?;
+        rmp_serde::from_slice(&body).map_err(Error::from)
+    }
+
     /// Reads the response as a String.
     pub async fn text(&self) -> Result<String, Error> {
         let promise = self.0.text().unwrap();
@@ -202,6 +305,33 @@ impl ResponseBuilder {
             .body(Some(json.as_str()))
     }
 
+    /// A convenience method to set CBOR as response body
+    ///
+    /// # Note
+    ///
+    /// This method also sets the `Content-Type` header to `application/cbor`
+    #[cfg(feature = "cbor")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cbor")))]
+    pub fn cbor<T: serde::Serialize + ?Sized>(self, value: &T) -> Result<Response, Error> {
+        let mut cbor = Vec::new();
+        ciborium::into_writer(value, &mut cbor)?;
+        self.header("Content-Type", "application/cbor")
+            .body(Some(cbor.as_mut_slice()))
+    }
+
+    /// A convenience method to set MessagePack as response body
+    ///
+    /// # Note
+    ///
+    /// This method also sets the `Content-Type` header to `application/msgpack`
+    #[cfg(feature = "msgpack")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "msgpack")))]
+    pub fn msgpack<T: serde::Serialize + ?Sized>(self, value: &T) -> Result<Response, Error> {
+        let mut msgpack = rmp_serde::to_vec_named(value)?;
+        self.header("Content-Type", "application/msgpack")
+            .body(Some(msgpack.as_mut_slice()))
+    }
+
     /// Set the response body and return the response
     pub fn body<T>(mut self, data: T) -> Result<Response, Error>
     where
@@ -0,0 +1,115 @@
+use std::fmt;
+
+/// An [HTTP status code](https://developer.mozilla.org/en-US/docs/Web/HTTP/Status) of a
+/// [`Response`](crate::http::Response).
+///
+/// Unlike [`http::StatusCode`], this type accepts any `u16`, including the status `0` that the
+/// `fetch` API reports for opaque and network error responses.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StatusCode(u16);
+
+impl StatusCode {
+    /// Create a status code from a `u16`.
+    pub const fn from_u16(code: u16) -> Self {
+        Self(code)
+    }
+
+    /// Returns the status code as a `u16`.
+    pub const fn as_u16(&self) -> u16 {
+        self.0
+    }
+
+    /// Check if the status code is within `100 - 199`.
+    pub const fn is_informational(&self) -> bool {
+        100 <= self.0 && self.0 < 200
+    }
+
+    /// Check if the status code is within `200 - 299`.
+    pub const fn is_success(&self) -> bool {
+        200 <= self.0 && self.0 < 300
+    }
+
+    /// Check if the status code is within `300 - 399`.
+    pub const fn is_redirection(&self) -> bool {
+        300 <= self.0 && self.0 < 400
+    }
+
+    /// Check if the status code is within `400 - 499`.
+    pub const fn is_client_error(&self) -> bool {
+        400 <= self.0 && self.0 < 500
+    }
+
+    /// Check if the status code is within `500 - 599`.
+    pub const fn is_server_error(&self) -> bool {
+        500 <= self.0 && self.0 < 600
+    }
+
+    /// The standardised reason phrase for this status code, e.g. `Not Found` for `404`.
+    ///
+    /// Returns `None` for unknown status codes. Note that the server may have sent a different
+    /// phrase, which is available through [`Response::status_text`](crate::http::Response::status_text).
+    pub fn canonical_reason(&self) -> Option<&'static str> {
+        http::StatusCode::from_u16(self.0)
+            .ok()
+            .and_then(|code| code.canonical_reason())
+    }
+}
+
+impl From<u16> for StatusCode {
+    fn from(code: u16) -> Self {
+        Self(code)
+    }
+}
+
+impl From<StatusCode> for u16 {
+    fn from(code: StatusCode) -> Self {
+        code.0
+    }
+}
+
+impl PartialEq<u16> for StatusCode {
+    fn eq(&self, other: &u16) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<StatusCode> for u16 {
+    fn eq(&self, other: &StatusCode) -> bool {
+        *self == other.0
+    }
+}
+
+impl fmt::Display for StatusCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.canonical_reason() {
+            Some(reason) => write!(f, "{} {}", self.0, reason),
+            None => write!(f, "{}", self.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_code_classes() {
+        assert!(StatusCode::from(101).is_informational());
+        assert!(StatusCode::from(204).is_success());
+        assert!(StatusCode::from(304).is_redirection());
+        assert!(StatusCode::from(404).is_client_error());
+        assert!(StatusCode::from(503).is_server_error());
+
+        let opaque = StatusCode::from(0);
+        assert!(!opaque.is_success());
+        assert!(!opaque.is_client_error());
+        assert!(!opaque.is_server_error());
+    }
+
+    #[test]
+    fn status_code_display() {
+        assert_eq!(StatusCode::from(404).to_string(), "404 Not Found");
+        assert_eq!(StatusCode::from(0).to_string(), "0");
+        assert_eq!(StatusCode::from(599).canonical_reason(), None);
+    }
+}
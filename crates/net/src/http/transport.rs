@@ -0,0 +1,101 @@
+use crate::http::{Request, Response};
+use crate::Error;
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+
+/// A boxed future returned by a [`Transport`].
+pub type TransportFuture = Pin<Box<dyn Future<Output = Result<Response, Error>>>>;
+
+/// Sends a [`Request`] and resolves to its [`Response`].
+///
+/// By default requests are sent with the global `fetch` function. A different transport, such as
+/// the [mock transport](crate::http::mock) used in tests, can be installed for the current thread
+/// with [`set_transport`].
+pub trait Transport {
+    /// Send the request.
+    fn send(&self, request: Request) -> TransportFuture;
+}
+
+thread_local! {
+    /// The installed transports, the current one last, with the id of their guard.
+    static TRANSPORTS: RefCell<Vec<(u64, Rc<dyn Transport>)>> = const { RefCell::new(Vec::new()) };
+    static NEXT_GUARD: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Replaces the transport used by [`Request::send`] on the current thread.
+///
+/// The previous transport is restored when the returned guard is dropped. Guards may be dropped
+/// in any order: the current transport is always the most recently installed one whose guard is
+/// still alive.
+pub fn set_transport(transport: impl Transport + 'static) -> TransportGuard {
+    let id = NEXT_GUARD.with(|next| next.replace(next.get() + 1));
+    TRANSPORTS.with(|transports| transports.borrow_mut().push((id, Rc::new(transport))));
+    TransportGuard { id }
+}
+
+pub(crate) fn current_transport() -> Option<Rc<dyn Transport>> {
+    TRANSPORTS.with(|transports| {
+        let transports = transports.borrow();
+        transports.last().map(|(_, transport)| Rc::clone(transport))
+    })
+}
+
+/// Restores the previous transport when dropped. Returned by [`set_transport`].
+#[must_use = "the transport is reset when the guard is dropped"]
+pub struct TransportGuard {
+    id: u64,
+}
+
+impl Drop for TransportGuard {
+    fn drop(&mut self) {
+        TRANSPORTS.with(|transports| transports.borrow_mut().retain(|(id, _)| *id != self.id));
+    }
+}
+
+impl fmt::Debug for TransportGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TransportGuard").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Unused;
+
+    impl Transport for Unused {
+        fn send(&self, _request: Request) -> TransportFuture {
+            unreachable!()
+        }
+    }
+
+    fn current() -> *const () {
+        current_transport().map_or(std::ptr::null(), |transport| {
+            Rc::as_ptr(&transport) as *const ()
+        })
+    }
+
+    #[test]
+    fn guards_restore_the_previous_transport() {
+        let outer = set_transport(Unused);
+        let outer_transport = current();
+        let inner = set_transport(Unused);
+        let inner_transport = current();
+        assert_ne!(inner_transport, outer_transport);
+
+        drop(inner);
+        assert_eq!(current(), outer_transport);
+        let inner = set_transport(Unused);
+        let inner_transport = current();
+
+        // Out of order: the inner transport stays current until its own guard is dropped.
+        drop(outer);
+        assert_eq!(current(), inner_transport);
+        drop(inner);
+        assert!(current_transport().is_none());
+    }
+}
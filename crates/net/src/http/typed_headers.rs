@@ -0,0 +1,218 @@
+use mime::Mime;
+use std::time::{Duration, SystemTime};
+
+/// The value of an `Authorization` header: an authentication scheme followed by credentials.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Authorization {
+    /// The authentication scheme, e.g. `Bearer` or `Basic`.
+    pub scheme: String,
+    /// The credentials, e.g. a token.
+    pub credentials: String,
+}
+
+impl Authorization {
+    /// The token of a `Bearer` authorization.
+    pub fn bearer_token(&self) -> Option<&str> {
+        if self.scheme.eq_ignore_ascii_case("bearer") {
+            Some(&self.credentials)
+        } else {
+            None
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let (scheme, credentials) = value.split_once(' ').unwrap_or((value, ""));
+        if scheme.is_empty() {
+            return None;
+        }
+        Some(Self {
+            scheme: scheme.to_string(),
+            credentials: credentials.trim().to_string(),
+        })
+    }
+}
+
+/// The directives of a `Cache-Control` header.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct CacheControl {
+    /// The `(name, argument)` pairs, in order. Names are lowercase.
+    pub directives: Vec<(String, Option<String>)>,
+}
+
+impl CacheControl {
+    /// Whether the given directive is present, e.g. `no-store`.
+    pub fn has(&self, directive: &str) -> bool {
+        self.directives
+            .iter()
+            .any(|(name, _)| name.eq_ignore_ascii_case(directive))
+    }
+
+    /// The argument of the given directive, e.g. `"60"` for `max-age=60`.
+    pub fn get(&self, directive: &str) -> Option<&str> {
+        self.directives
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(directive))
+            .and_then(|(_, argument)| argument.as_deref())
+    }
+
+    /// Whether the `no-cache` directive is present.
+    pub fn no_cache(&self) -> bool {
+        self.has("no-cache")
+    }
+
+    /// Whether the `no-store` directive is present.
+    pub fn no_store(&self) -> bool {
+        self.has("no-store")
+    }
+
+    /// The `max-age` directive.
+    pub fn max_age(&self) -> Option<Duration> {
+        self.get("max-age")
+            .and_then(|secs| secs.parse().ok())
+            .map(Duration::from_secs)
+    }
+
+    fn parse(value: &str) -> Self {
+        let directives = value
+            .split(',')
+            .map(str::trim)
+            .filter(|directive| !directive.is_empty())
+            .map(|directive| match directive.split_once('=') {
+                Some((name, argument)) => (
+                    name.trim().to_ascii_lowercase(),
+                    Some(argument.trim().trim_matches('"').to_string()),
+                ),
+                None => (directive.to_ascii_lowercase(), None),
+            })
+            .collect();
+        Self { directives }
+    }
+}
+
+/// The value of an `ETag` header.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ETag {
+    /// Whether this is a weak validator (`W/"..."`).
+    pub weak: bool,
+    /// The opaque tag, without the quotes.
+    pub tag: String,
+}
+
+impl ETag {
+    fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let (weak, tag) = match value.strip_prefix("W/") {
+            Some(tag) => (true, tag),
+            None => (false, value),
+        };
+        let tag = tag.strip_prefix('"')?.strip_suffix('"')?;
+        Some(Self {
+            weak,
+            tag: tag.to_string(),
+        })
+    }
+}
+
+pub(crate) fn content_type(value: &str) -> Option<Mime> {
+    value.parse().ok()
+}
+
+pub(crate) fn content_length(value: &str) -> Option<u64> {
+    value.trim().parse().ok()
+}
+
+pub(crate) fn authorization(value: &str) -> Option<Authorization> {
+    Authorization::parse(value)
+}
+
+pub(crate) fn cache_control(value: &str) -> Option<CacheControl> {
+    Some(CacheControl::parse(value))
+}
+
+pub(crate) fn etag(value: &str) -> Option<ETag> {
+    ETag::parse(value)
+}
+
+pub(crate) fn last_modified(value: &str) -> Option<SystemTime> {
+    httpdate::parse_http_date(value.trim()).ok()
+}
+
+/// Adds the typed accessors to a header collection with a `get(&self, &str)` method returning an
+/// optional string.
+macro_rules! typed_header_accessors {
+    () => {
+        /// The `Content-Type` header, parsed as a [`Mime`](crate::http::Mime).
+        pub fn content_type(&self) -> Option<crate::http::Mime> {
+            crate::http::typed_headers::content_type(self.get("Content-Type").as_deref()?)
+        }
+
+        /// The `Content-Length` header.
+        pub fn content_length(&self) -> Option<u64> {
+            crate::http::typed_headers::content_length(self.get("Content-Length").as_deref()?)
+        }
+
+        /// The `Authorization` header.
+        pub fn authorization(&self) -> Option<crate::http::Authorization> {
+            crate::http::typed_headers::authorization(self.get("Authorization").as_deref()?)
+        }
+
+        /// The `Cache-Control` header.
+        pub fn cache_control(&self) -> Option<crate::http::CacheControl> {
+            crate::http::typed_headers::cache_control(self.get("Cache-Control").as_deref()?)
+        }
+
+        /// The `ETag` header.
+        pub fn etag(&self) -> Option<crate::http::ETag> {
+            crate::http::typed_headers::etag(self.get("ETag").as_deref()?)
+        }
+
+        /// The `Last-Modified` header, parsed as an HTTP date.
+        pub fn last_modified(&self) -> Option<std::time::SystemTime> {
+            crate::http::typed_headers::last_modified(self.get("Last-Modified").as_deref()?)
+        }
+    };
+}
+
+pub(crate) use typed_header_accessors;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_typed_values() {
+        assert_eq!(
+            content_type("application/json; charset=utf-8")
+                .unwrap()
+                .essence_str(),
+            "application/json"
+        );
+        assert_eq!(content_length(" 42 "), Some(42));
+        assert_eq!(
+            authorization("Bearer abc").unwrap().bearer_token(),
+            Some("abc")
+        );
+        assert_eq!(
+            etag(r#"W/"xyz""#),
+            Some(ETag {
+                weak: true,
+                tag: "xyz".to_string()
+            })
+        );
+        assert_eq!(etag("xyz"), None);
+        assert_eq!(
+            last_modified("Thu, 01 Jan 1970 00:01:00 GMT"),
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(60))
+        );
+    }
+
+    #[test]
+    fn parse_cache_control() {
+        let cache_control = cache_control("No-Cache, max-age=60, private=\"x\"").unwrap();
+        assert!(cache_control.no_cache());
+        assert!(!cache_control.no_store());
+        assert_eq!(cache_control.max_age(), Some(Duration::from_secs(60)));
+        assert_eq!(cache_control.get("private"), Some("x"));
+    }
+}
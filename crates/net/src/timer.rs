@@ -0,0 +1,12 @@
+use gloo_timers::future::TimeoutFuture;
+use std::time::Duration;
+
+/// The duration in milliseconds, saturating at the longest delay a timer supports.
+pub(crate) fn millis(duration: Duration) -> u32 {
+    duration.as_millis().try_into().unwrap_or(u32::MAX)
+}
+
+/// A timer that fires after the duration, or after the longest supported delay if it is longer.
+pub(crate) fn timeout(duration: Duration) -> TimeoutFuture {
+    TimeoutFuture::new(millis(duration))
+}
@@ -0,0 +1,161 @@
+//! Binary messages received as [`Blob`]s.
+//!
+//! A [`WebSocket`] copies every binary message into wasm memory. [`BlobWebSocket`] wraps one so
+//! binary messages are received as [`Blob`]s instead, left in JavaScript memory to be read later,
+//! if at all. This avoids copying large messages. Messages are still received in order.
+//!
+//! # Example
+//!
+//! ```rust
+//! use gloo_net::websocket::blob::{BlobMessage, BlobWebSocket};
+//! use gloo_net::websocket::futures::WebSocket;
+//! use wasm_bindgen_futures::spawn_local;
+//! use futures::StreamExt;
+//!
+//! # fn no_run() {
+//! let ws = WebSocket::open("wss://media.example.com/frames").unwrap();
+//! let mut ws = BlobWebSocket::new(ws);
+//!
+//! spawn_local(async move {
+//!     while let Some(Ok(message)) = ws.next().await {
+//!         match message {
+//!             BlobMessage::Blob(frame) => { /* `frame.size()` bytes, not read yet */ }
+//!             BlobMessage::Text(text) => { /* ... */ }
+//!         }
+//!     }
+//! })
+//! # }
+//! ```
+use crate::websocket::futures::{Received, WebSocket};
+use crate::websocket::{Message, WebSocketError};
+use futures_core::{ready, Stream};
+use futures_sink::Sink;
+use gloo_file::Blob;
+use pin_project::pin_project;
+use std::fmt;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Message sent to and received from a [`BlobWebSocket`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum BlobMessage {
+    /// String message
+    Text(String),
+    /// Binary message, not read into wasm memory
+    Blob(Blob),
+}
+
+/// A [`WebSocket`] that receives binary messages as [`Blob`]s.
+///
+/// It is a `Stream` of [`BlobMessage`]s and a `Sink` for them. Errors are reported as the
+/// underlying [`WebSocket`] reports them.
+#[pin_project]
+pub struct BlobWebSocket {
+    #[pin]
+    ws: WebSocket,
+}
+
+impl BlobWebSocket {
+    /// Wrap a WebSocket.
+    ///
+    /// Wrap it right after opening it, so every binary message is received as a [`Blob`].
+    pub fn new(ws: WebSocket) -> Self {
+        ws.receive_blobs();
+        Self { ws }
+    }
+
+    /// The wrapped WebSocket.
+    pub fn get_ref(&self) -> &WebSocket {
+        &self.ws
+    }
+}
+
+impl fmt::Debug for BlobWebSocket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BlobWebSocket")
+            .field("state", &self.ws.state())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Stream for BlobWebSocket {
+    type Item = Result<BlobMessage, WebSocketError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let message = match ready!(self.project().ws.poll_received(cx)) {
+            Some(Ok(Received::Blob(blob))) => BlobMessage::Blob(blob.into()),
+            Some(Ok(Received::Message(Message::Text(text)))) => BlobMessage::Text(text),
+            // received before the binary type was changed
+            Some(Ok(Received::Message(Message::Bytes(bytes)))) => {
+                BlobMessage::Blob(Blob::new(bytes.as_slice()))
+            }
+            Some(Err(e)) => return Poll::Ready(Some(Err(e))),
+            None => return Poll::Ready(None),
+        };
+        Poll::Ready(Some(Ok(message)))
+    }
+}
+
+impl Sink<BlobMessage> for BlobWebSocket {
+    type Error = WebSocketError;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.project().ws.poll_ready(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: BlobMessage) -> Result<(), Self::Error> {
+        match item {
+            BlobMessage::Text(text) => self.project().ws.start_send(Message::Text(text)),
+            BlobMessage::Blob(blob) => self.ws.send_blob(blob.as_ref()),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.project().ws.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.project().ws.poll_close(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{SinkExt, StreamExt};
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    async fn blobs_are_received_in_order() {
+        let ws_echo_server_url =
+            option_env!("WS_ECHO_SERVER_URL").expect("Did you set WS_ECHO_SERVER_URL?");
+
+        let mut ws = BlobWebSocket::new(WebSocket::open(ws_echo_server_url).unwrap());
+        let _ = ws.next().await;
+
+        ws.send(BlobMessage::Blob(Blob::new(&[1, 2, 3][..])))
+            .await
+            .unwrap();
+        ws.send(BlobMessage::Text("after".to_string()))
+            .await
+            .unwrap();
+        ws.send(BlobMessage::Blob(Blob::new(&b"blob"[..])))
+            .await
+            .unwrap();
+
+        match ws.next().await.unwrap().unwrap() {
+            BlobMessage::Blob(blob) => assert_eq!(blob.size(), 3),
+            msg => panic!("expected a Blob, received {:?}", msg),
+        }
+        assert_eq!(
+            ws.next().await.unwrap().unwrap(),
+            BlobMessage::Text("after".to_string())
+        );
+        match ws.next().await.unwrap().unwrap() {
+            BlobMessage::Blob(blob) => assert_eq!(blob.size(), 4),
+            msg => panic!("expected a Blob, received {:?}", msg),
+        }
+    }
+}
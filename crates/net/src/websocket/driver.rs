@@ -0,0 +1,66 @@
+//! The loop of the background tasks that own a websocket, shared by the layers built on top of
+//! one, such as the RPC client and the multiplexer.
+use crate::websocket::{Message, WebSocketError};
+use futures_channel::mpsc;
+use futures_core::Stream;
+use futures_sink::Sink;
+use std::convert::Infallible;
+use std::future::poll_fn;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// What woke a background task up.
+pub(crate) enum Step {
+    /// The next item of the websocket, `None` once it is closed.
+    Inbound(Option<Result<Message, WebSocketError>>),
+    /// The next message to send, `None` once all its senders are dropped.
+    Outbound(Option<Message>),
+}
+
+/// Waits for the next item of the websocket or, if `outbound` is given, the next message to send.
+///
+/// Received items come first, so a fast sender can't hold up the connection.
+pub(crate) async fn next_step<S>(
+    ws: &mut S,
+    outbound: Option<&mut mpsc::UnboundedReceiver<Message>>,
+) -> Step
+where
+    S: Stream<Item = Result<Message, WebSocketError>> + Unpin,
+{
+    match next_step_or(ws, outbound, |_| Poll::<Infallible>::Pending).await {
+        Ok(step) => step,
+        Err(never) => match never {},
+    }
+}
+
+/// Like [`next_step`], but also waits for `other`, e.g. a timer, which is polled last.
+pub(crate) async fn next_step_or<S, T>(
+    ws: &mut S,
+    mut outbound: Option<&mut mpsc::UnboundedReceiver<Message>>,
+    mut other: impl FnMut(&mut Context<'_>) -> Poll<T>,
+) -> Result<Step, T>
+where
+    S: Stream<Item = Result<Message, WebSocketError>> + Unpin,
+{
+    poll_fn(|cx| {
+        if let Poll::Ready(item) = Pin::new(&mut *ws).poll_next(cx) {
+            return Poll::Ready(Ok(Step::Inbound(item)));
+        }
+        if let Some(outbound) = outbound.as_mut() {
+            if let Poll::Ready(message) = Pin::new(&mut **outbound).poll_next(cx) {
+                return Poll::Ready(Ok(Step::Outbound(message)));
+            }
+        }
+        other(cx).map(Err)
+    })
+    .await
+}
+
+/// Sends a message once the websocket is ready for it.
+pub(crate) async fn send<S>(ws: &mut S, message: Message) -> Result<(), WebSocketError>
+where
+    S: Sink<Message, Error = WebSocketError> + Unpin,
+{
+    poll_fn(|cx| Pin::new(&mut *ws).poll_ready(cx)).await?;
+    Pin::new(ws).start_send(message)
+}
@@ -28,18 +28,27 @@
 //! # }
 //! ```
 use crate::js_to_js_error;
-use crate::websocket::{events::CloseEvent, Message, State, WebSocketError};
+use crate::websocket::heartbeat::HeartbeatState;
+use crate::websocket::{
+    events::CloseEvent, Heartbeat, Message, State, StateChange, WebSocketError,
+};
 use futures_channel::mpsc;
 use futures_core::{ready, Stream};
 use futures_sink::Sink;
+use gloo_timers::future::TimeoutFuture;
 use gloo_utils::errors::JsError;
 use pin_project::{pin_project, pinned_drop};
 use std::cell::RefCell;
+use std::fmt;
+use std::future::poll_fn;
+use std::future::Future;
 use std::pin::Pin;
 use std::rc::Rc;
 use std::task::{Context, Poll, Waker};
+use std::time::Duration;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::spawn_local;
 use web_sys::{BinaryType, MessageEvent};
 
 /// Wrapper around browser's WebSocket API.
@@ -50,6 +59,12 @@ pub struct WebSocket {
     sink_waker: Rc<RefCell<Option<Waker>>>,
     #[pin]
     message_receiver: mpsc::UnboundedReceiver<StreamMessage>,
+    sender: mpsc::UnboundedSender<StreamMessage>,
+    heartbeat: Rc<RefCell<Option<Rc<HeartbeatState>>>>,
+    closed: Rc<Closed>,
+    high_water_mark: Option<u32>,
+    /// Wakes the sink to check whether the send buffer has drained below the high-water mark.
+    drain_timer: Option<TimeoutFuture>,
     #[allow(clippy::type_complexity)]
     closures: (
         Closure<dyn FnMut()>,
@@ -60,7 +75,7 @@ pub struct WebSocket {
     /// Leftover bytes when using `AsyncRead`.
     ///
     /// These bytes are drained and returned in subsequent calls to `poll_read`.
-    #[cfg(feature = "io-util")]
+    #[cfg(any(feature = "io-util", feature = "tokio-io"))]
     pub(super) read_pending_bytes: Option<Vec<u8>>, // Same size as `Vec<u8>` alone thanks to niche optimization
 }
 
@@ -126,14 +141,19 @@ impl WebSocket {
 
         // We rely on this because the other type Blob can be converted to Vec<u8> only through a
         // promise which makes it awkward to use in our event callbacks where we want to guarantee
-        // the order of the events stays the same.
+        // the order of the events stays the same. Blobs are only received as is, by a
+        // `BlobWebSocket`.
         ws.set_binary_type(BinaryType::Arraybuffer);
 
         let (sender, receiver) = mpsc::unbounded();
+        let heartbeat: Rc<RefCell<Option<Rc<HeartbeatState>>>> = Rc::new(RefCell::new(None));
+        let closed = Closed::new(&ws)?;
 
         let open_callback: Closure<dyn FnMut()> = {
             let waker = Rc::clone(&waker);
+            let closed = Rc::clone(&closed);
             Closure::wrap(Box::new(move || {
+                closed.notify(StateChange::Open);
                 if let Some(waker) = waker.borrow_mut().take() {
                     waker.wake();
                 }
@@ -149,8 +169,19 @@ impl WebSocket {
 
         let message_callback: Closure<dyn FnMut(MessageEvent)> = {
             let sender = sender.clone();
+            let heartbeat = Rc::clone(&heartbeat);
             Closure::wrap(Box::new(move |e: MessageEvent| {
                 let msg = parse_message(e);
+                let is_pong = match &msg {
+                    Received::Message(msg) => heartbeat
+                        .borrow()
+                        .as_ref()
+                        .map_or(false, |heartbeat| heartbeat.receive(msg)),
+                    Received::Blob(_) => false,
+                };
+                if is_pong {
+                    return;
+                }
                 let _ = sender.unbounded_send(StreamMessage::Message(msg));
             }) as Box<dyn FnMut(MessageEvent)>)
         };
@@ -173,6 +204,7 @@ impl WebSocket {
             .map_err(js_to_js_error)?;
 
         let close_callback: Closure<dyn FnMut(web_sys::CloseEvent)> = {
+            let sender = sender.clone();
             Closure::wrap(Box::new(move |e: web_sys::CloseEvent| {
                 let close_event = CloseEvent {
                     code: e.code(),
@@ -195,13 +227,18 @@ impl WebSocket {
             ws,
             sink_waker: waker,
             message_receiver: receiver,
+            sender,
+            heartbeat,
+            closed,
+            high_water_mark: None,
+            drain_timer: None,
             closures: (
                 open_callback,
                 message_callback,
                 error_callback,
                 close_callback,
             ),
-            #[cfg(feature = "io-util")]
+            #[cfg(any(feature = "io-util", feature = "tokio-io"))]
             read_pending_bytes: None,
         })
     }
@@ -211,15 +248,91 @@ impl WebSocket {
     /// See the [MDN Documentation](https://developer.mozilla.org/en-US/docs/Web/API/WebSocket/close#parameters)
     /// to learn about parameters passed to this function and when it can return an `Err(_)`
     pub fn close(self, code: Option<u16>, reason: Option<&str>) -> Result<(), JsError> {
-        let result = match (code, reason) {
-            (None, None) => self.ws.close(),
-            (Some(code), None) => self.ws.close_with_code(code),
-            (Some(code), Some(reason)) => self.ws.close_with_code_and_reason(code, reason),
-            // default code is 1005 so we use it,
-            // see: https://developer.mozilla.org/en-US/docs/Web/API/WebSocket/close#parameters
-            (None, Some(reason)) => self.ws.close_with_code_and_reason(1005, reason),
-        };
-        result.map_err(js_to_js_error)
+        close(&self.ws, &self.closed, code, reason)
+    }
+
+    /// Closes the websocket and waits for the closing handshake to complete.
+    ///
+    /// See [`CloseHandle::close_gracefully`].
+    pub async fn close_gracefully(
+        &self,
+        code: Option<u16>,
+        reason: Option<&str>,
+        timeout: Duration,
+    ) -> Result<Option<CloseEvent>, JsError> {
+        self.close_handle()
+            .close_gracefully(code, reason, timeout)
+            .await
+    }
+
+    /// A handle that can close the websocket, e.g. after it has been split with
+    /// `StreamExt::split`.
+    pub fn close_handle(&self) -> CloseHandle {
+        CloseHandle {
+            ws: self.ws.clone(),
+            closed: Rc::clone(&self.closed),
+        }
+    }
+
+    /// Starts sending pings to detect a dead connection, replacing any previous heartbeat.
+    ///
+    /// If no pong is received in time, the stream yields [`WebSocketError::HeartbeatTimeout`]
+    /// and the websocket is closed. A connection that is not open by the time of the first
+    /// ping is also considered dead. See [`Heartbeat`].
+    pub fn enable_heartbeat(&mut self, heartbeat: Heartbeat) {
+        let state = Rc::new(HeartbeatState::new(heartbeat));
+        *self.heartbeat.borrow_mut() = Some(Rc::clone(&state));
+
+        let ws = self.ws.clone();
+        let sender = self.sender.clone();
+        let current = Rc::downgrade(&self.heartbeat);
+        spawn_local(async move {
+            let is_alive = || {
+                let is_current = current.upgrade().map_or(false, |current| {
+                    current
+                        .borrow()
+                        .as_ref()
+                        .map_or(false, |current| Rc::ptr_eq(current, &state))
+                });
+                is_current && ws.ready_state() <= web_sys::WebSocket::OPEN
+            };
+            let send_ping = |ping: &Message| {
+                if ws.ready_state() == web_sys::WebSocket::OPEN {
+                    let _ = send_message(&ws, ping);
+                }
+            };
+
+            if state.run(is_alive, send_ping).await {
+                let _ = sender.unbounded_send(StreamMessage::HeartbeatTimeout);
+                let _ = ws.close();
+            }
+        });
+    }
+
+    /// Stops the heartbeat enabled by [`WebSocket::enable_heartbeat`].
+    pub fn disable_heartbeat(&mut self) {
+        *self.heartbeat.borrow_mut() = None;
+    }
+
+    /// Limits how much data can be queued for sending.
+    ///
+    /// Once more than `bytes` bytes are waiting to be transmitted, the [`Sink`] stops being ready
+    /// until the browser has sent enough of them. This keeps memory use bounded when forwarding a
+    /// stream into the socket faster than the network can carry it. `None`, the default, never
+    /// waits.
+    ///
+    /// Browsers do not signal when the send buffer drains, so it is checked every few
+    /// milliseconds while the sink is waiting.
+    pub fn set_high_water_mark(&mut self, bytes: Option<u32>) {
+        self.high_water_mark = bytes;
+    }
+
+    /// The number of bytes that have been sent but not yet transmitted to the network.
+    ///
+    /// See the [MDN Documentation](https://developer.mozilla.org/en-US/docs/Web/API/WebSocket/bufferedAmount)
+    /// to learn more.
+    pub fn buffered_amount(&self) -> u32 {
+        self.ws.buffered_amount()
     }
 
     /// The current state of the websocket.
@@ -234,6 +347,54 @@ impl WebSocket {
         }
     }
 
+    /// A stream of the state transitions of the websocket, starting with its current state, so
+    /// that e.g. a status indicator does not have to poll [`WebSocket::state`].
+    ///
+    /// [`StateChange::Closed`] carries the code and reason of the `close` event, and ends the
+    /// stream. Browsers have no event for the start of a closing handshake, so
+    /// [`StateChange::Closing`] is only yielded when this side starts it, with
+    /// [`WebSocket::close`] or a [`CloseHandle`]. Errors are not states, the `close` event that
+    /// follows them is reported.
+    ///
+    /// ```rust
+    /// use gloo_net::websocket::futures::WebSocket;
+    /// use gloo_net::websocket::StateChange;
+    /// use futures::StreamExt;
+    ///
+    /// # async fn no_run() {
+    /// let ws = WebSocket::open("wss://echo.websocket.org").unwrap();
+    /// let mut state_changes = ws.state_changes();
+    ///
+    /// while let Some(change) = state_changes.next().await {
+    ///     match change {
+    ///         StateChange::Closed(event) => { /* closed with `event.code` and `event.reason` */ }
+    ///         other => { /* `other.state()` */ }
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn state_changes(&self) -> StateChanges {
+        let (sender, receiver) = mpsc::unbounded();
+        let current = match self.state() {
+            State::Connecting => Some(StateChange::Connecting),
+            State::Open => Some(StateChange::Open),
+            State::Closing => Some(StateChange::Closing),
+            // the close event may not have been dispatched yet
+            State::Closed => self.closed.event.borrow().clone().map(StateChange::Closed),
+        };
+        if let Some(current) = current {
+            let _ = sender.unbounded_send(current);
+        }
+        if self.closed.event.borrow().is_none() {
+            self.closed.watchers.borrow_mut().push(sender);
+        }
+
+        StateChanges {
+            receiver,
+            last: None,
+        }
+    }
+
     /// The extensions in use.
     pub fn extensions(&self) -> String {
         self.ws.extensions()
@@ -243,6 +404,257 @@ impl WebSocket {
     pub fn protocol(&self) -> String {
         self.ws.protocol()
     }
+
+    /// Receives binary messages as blobs from now on.
+    #[cfg(feature = "blob")]
+    pub(super) fn receive_blobs(&self) {
+        self.ws.set_binary_type(BinaryType::Blob);
+    }
+
+    #[cfg(feature = "blob")]
+    pub(super) fn send_blob(&self, blob: &web_sys::Blob) -> Result<(), WebSocketError> {
+        self.ws
+            .send_with_blob(blob)
+            .map_err(|e| WebSocketError::MessageSendError(js_to_js_error(e)))
+    }
+
+    /// Polls the next received message, which may be a blob, or error.
+    pub(super) fn poll_received(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Received, WebSocketError>>> {
+        let msg = ready!(self.project().message_receiver.poll_next(cx));
+        match msg {
+            Some(StreamMessage::Message(msg)) => Poll::Ready(Some(Ok(msg))),
+            Some(StreamMessage::ErrorEvent) => {
+                Poll::Ready(Some(Err(WebSocketError::ConnectionError)))
+            }
+            Some(StreamMessage::CloseEvent(e)) => {
+                Poll::Ready(Some(Err(WebSocketError::ConnectionClose(e))))
+            }
+            Some(StreamMessage::HeartbeatTimeout) => {
+                Poll::Ready(Some(Err(WebSocketError::HeartbeatTimeout)))
+            }
+            Some(StreamMessage::ConnectionClose) => Poll::Ready(None),
+            None => Poll::Ready(None),
+        }
+    }
+}
+
+fn close(
+    ws: &web_sys::WebSocket,
+    closed: &Closed,
+    code: Option<u16>,
+    reason: Option<&str>,
+) -> Result<(), JsError> {
+    let result = match (code, reason) {
+        (None, None) => ws.close(),
+        (Some(code), None) => ws.close_with_code(code),
+        (Some(code), Some(reason)) => ws.close_with_code_and_reason(code, reason),
+        // default code is 1005 so we use it,
+        // see: https://developer.mozilla.org/en-US/docs/Web/API/WebSocket/close#parameters
+        (None, Some(reason)) => ws.close_with_code_and_reason(1005, reason),
+    };
+    result.map_err(js_to_js_error)?;
+    if ws.ready_state() == web_sys::WebSocket::CLOSING {
+        closed.notify(StateChange::Closing);
+    }
+    Ok(())
+}
+
+/// The `close` event of a websocket, shared with its [`CloseHandle`]s, and the [`StateChanges`]
+/// streams to notify of transitions.
+///
+/// It listens for the event itself, so that the server's event is still received by the handles
+/// after the [`WebSocket`] is dropped.
+struct Closed {
+    ws: web_sys::WebSocket,
+    listener: Closure<dyn FnMut(web_sys::CloseEvent)>,
+    event: RefCell<Option<CloseEvent>>,
+    wakers: RefCell<Vec<Waker>>,
+    watchers: RefCell<Vec<mpsc::UnboundedSender<StateChange>>>,
+}
+
+impl Closed {
+    fn new(ws: &web_sys::WebSocket) -> Result<Rc<Self>, JsError> {
+        let closed = Rc::new_cyclic(|closed: &std::rc::Weak<Self>| {
+            let closed = closed.clone();
+            let listener = Closure::wrap(Box::new(move |e: web_sys::CloseEvent| {
+                // the event dispatched when the `WebSocket` is dropped is not the server's
+                if !e.is_trusted() {
+                    return;
+                }
+                if let Some(closed) = closed.upgrade() {
+                    closed.set(CloseEvent {
+                        code: e.code(),
+                        reason: e.reason(),
+                        was_clean: e.was_clean(),
+                    });
+                }
+            }) as Box<dyn FnMut(web_sys::CloseEvent)>);
+            Self {
+                ws: ws.clone(),
+                listener,
+                event: RefCell::new(None),
+                wakers: RefCell::new(Vec::new()),
+                watchers: RefCell::new(Vec::new()),
+            }
+        });
+        ws.add_event_listener_with_callback("close", closed.listener.as_ref().unchecked_ref())
+            .map_err(js_to_js_error)?;
+        Ok(closed)
+    }
+
+    fn set(&self, event: CloseEvent) {
+        *self.event.borrow_mut() = Some(event.clone());
+        for waker in self.wakers.borrow_mut().drain(..) {
+            waker.wake();
+        }
+        self.notify(StateChange::Closed(event));
+        // nothing happens after the close event
+        self.watchers.borrow_mut().clear();
+    }
+
+    fn notify(&self, change: StateChange) {
+        self.watchers
+            .borrow_mut()
+            .retain(|watcher| watcher.unbounded_send(change.clone()).is_ok());
+    }
+}
+
+impl Drop for Closed {
+    fn drop(&mut self) {
+        let _ = self
+            .ws
+            .remove_event_listener_with_callback("close", self.listener.as_ref().unchecked_ref());
+    }
+}
+
+/// A stream of the state transitions of a [`WebSocket`], starting with its current state.
+///
+/// It ends after [`StateChange::Closed`]. Returned by [`WebSocket::state_changes`].
+pub struct StateChanges {
+    receiver: mpsc::UnboundedReceiver<StateChange>,
+    last: Option<State>,
+}
+
+impl fmt::Debug for StateChanges {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StateChanges")
+            .field("last", &self.last)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Stream for StateChanges {
+    type Item = StateChange;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if self.last == Some(State::Closed) {
+                return Poll::Ready(None);
+            }
+            match ready!(Pin::new(&mut self.receiver).poll_next(cx)) {
+                // the initial state may be reported again by its event
+                Some(change) if Some(change.state()) == self.last => continue,
+                Some(change) => {
+                    self.last = Some(change.state());
+                    return Poll::Ready(Some(change));
+                }
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+/// A cloneable handle to close a [`WebSocket`].
+///
+/// Unlike [`WebSocket::close`], it does not need ownership of the websocket, so it can be used
+/// after the websocket has been split into a sink and a stream, or from another task.
+#[derive(Clone)]
+pub struct CloseHandle {
+    ws: web_sys::WebSocket,
+    closed: Rc<Closed>,
+}
+
+impl CloseHandle {
+    /// Closes the websocket.
+    ///
+    /// See [`WebSocket::close`].
+    pub fn close(&self, code: Option<u16>, reason: Option<&str>) -> Result<(), JsError> {
+        close(&self.ws, &self.closed, code, reason)
+    }
+
+    /// Closes the websocket and waits for the closing handshake to complete.
+    ///
+    /// Resolves to the `close` event, with the code and reason sent by the server, or to `None`
+    /// if it does not arrive within `timeout`. If the websocket is already closed, resolves to
+    /// its `close` event right away. Dropping the [`WebSocket`] meanwhile closes it, and the
+    /// server's `close` event is still waited for. Errors are the same as for
+    /// [`WebSocket::close`].
+    ///
+    /// ```rust
+    /// use gloo_net::websocket::futures::WebSocket;
+    /// use futures::StreamExt;
+    /// use std::time::Duration;
+    ///
+    /// # async fn no_run() {
+    /// let ws = WebSocket::open("wss://echo.websocket.org").unwrap();
+    /// let close_handle = ws.close_handle();
+    /// let (write, read) = ws.split();
+    ///
+    /// // ...
+    ///
+    /// match close_handle
+    ///     .close_gracefully(Some(1000), Some("done"), Duration::from_secs(5))
+    ///     .await
+    /// {
+    ///     Ok(Some(event)) => { /* the server acknowledged with `event.code` */ }
+    ///     Ok(None) => { /* timed out */ }
+    ///     Err(e) => { /* invalid code or reason */ }
+    /// }
+    /// # }
+    /// ```
+    pub async fn close_gracefully(
+        &self,
+        code: Option<u16>,
+        reason: Option<&str>,
+        timeout: Duration,
+    ) -> Result<Option<CloseEvent>, JsError> {
+        if self.ws.ready_state() != web_sys::WebSocket::CLOSED {
+            self.close(code, reason)?;
+        }
+
+        let mut timer = crate::timer::timeout(timeout);
+        let event = poll_fn(|cx| {
+            if let Some(event) = self.closed.event.borrow().clone() {
+                return Poll::Ready(Some(event));
+            }
+            if Pin::new(&mut timer).poll(cx).is_ready() {
+                self.closed
+                    .wakers
+                    .borrow_mut()
+                    .retain(|waker| !waker.will_wake(cx.waker()));
+                return Poll::Ready(None);
+            }
+            let mut wakers = self.closed.wakers.borrow_mut();
+            if !wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+                wakers.push(cx.waker().clone());
+            }
+            Poll::Pending
+        })
+        .await;
+        Ok(event)
+    }
+}
+
+impl fmt::Debug for CloseHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CloseHandle")
+            .field("url", &self.ws.url())
+            .field("close_event", &self.closed.event.borrow())
+            .finish()
+    }
 }
 
 impl TryFrom<web_sys::WebSocket> for WebSocket {
@@ -257,40 +669,70 @@ impl TryFrom<web_sys::WebSocket> for WebSocket {
 enum StreamMessage {
     ErrorEvent,
     CloseEvent(CloseEvent),
-    Message(Message),
+    Message(Received),
+    HeartbeatTimeout,
     ConnectionClose,
 }
 
-fn parse_message(event: MessageEvent) -> Message {
+/// A received message. Blobs are only received once the websocket is wrapped in a
+/// `BlobWebSocket`, or if the `binaryType` of the underlying socket was changed.
+#[derive(Clone)]
+pub(super) enum Received {
+    Message(Message),
+    Blob(#[cfg_attr(not(feature = "blob"), allow(dead_code))] web_sys::Blob),
+}
+
+fn parse_message(event: MessageEvent) -> Received {
     if let Ok(array_buffer) = event.data().dyn_into::<js_sys::ArrayBuffer>() {
         let array = js_sys::Uint8Array::new(&array_buffer);
-        Message::Bytes(array.to_vec())
+        Received::Message(Message::Bytes(array.to_vec()))
     } else if let Ok(txt) = event.data().dyn_into::<js_sys::JsString>() {
-        Message::Text(String::from(&txt))
+        Received::Message(Message::Text(String::from(&txt)))
+    } else if let Ok(blob) = event.data().dyn_into::<web_sys::Blob>() {
+        Received::Blob(blob)
     } else {
         unreachable!("message event, received Unknown: {:?}", event.data());
     }
 }
 
+/// How often the send buffer is checked while waiting for it to drain.
+const DRAIN_CHECK_INTERVAL_MS: u32 = 10;
+
+fn send_message(ws: &web_sys::WebSocket, message: &Message) -> Result<(), JsValue> {
+    match message {
+        Message::Bytes(bytes) => ws.send_with_u8_array(bytes),
+        Message::Text(message) => ws.send_with_str(message),
+    }
+}
+
 impl Sink<Message> for WebSocket {
     type Error = WebSocketError;
 
     fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        let ready_state = self.ws.ready_state();
+        let this = self.project();
+        let ready_state = this.ws.ready_state();
         if ready_state == 0 {
-            *self.sink_waker.borrow_mut() = Some(cx.waker().clone());
-            Poll::Pending
-        } else {
-            Poll::Ready(Ok(()))
+            *this.sink_waker.borrow_mut() = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+
+        if let Some(high_water_mark) = *this.high_water_mark {
+            while this.ws.ready_state() == web_sys::WebSocket::OPEN
+                && this.ws.buffered_amount() > high_water_mark
+            {
+                let timer = this
+                    .drain_timer
+                    .get_or_insert_with(|| TimeoutFuture::new(DRAIN_CHECK_INTERVAL_MS));
+                ready!(Pin::new(timer).poll(cx));
+                *this.drain_timer = None;
+            }
         }
+        *this.drain_timer = None;
+        Poll::Ready(Ok(()))
     }
 
     fn start_send(self: Pin<&mut Self>, item: Message) -> Result<(), Self::Error> {
-        let result = match item {
-            Message::Bytes(bytes) => self.ws.send_with_u8_array(&bytes),
-            Message::Text(message) => self.ws.send_with_str(&message),
-        };
-        match result {
+        match send_message(&self.ws, &item) {
             Ok(_) => Ok(()),
             Err(e) => Err(WebSocketError::MessageSendError(js_to_js_error(e))),
         }
@@ -309,16 +751,11 @@ impl Stream for WebSocket {
     type Item = Result<Message, WebSocketError>;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        let msg = ready!(self.project().message_receiver.poll_next(cx));
+        let msg = ready!(self.poll_received(cx));
         match msg {
-            Some(StreamMessage::Message(msg)) => Poll::Ready(Some(Ok(msg))),
-            Some(StreamMessage::ErrorEvent) => {
-                Poll::Ready(Some(Err(WebSocketError::ConnectionError)))
-            }
-            Some(StreamMessage::CloseEvent(e)) => {
-                Poll::Ready(Some(Err(WebSocketError::ConnectionClose(e))))
-            }
-            Some(StreamMessage::ConnectionClose) => Poll::Ready(None),
+            Some(Ok(Received::Message(msg))) => Poll::Ready(Some(Ok(msg))),
+            Some(Ok(Received::Blob(_))) => Poll::Ready(Some(Err(WebSocketError::UnexpectedBlob))),
+            Some(Err(e)) => Poll::Ready(Some(Err(e))),
             None => Poll::Ready(None),
         }
     }
@@ -354,6 +791,7 @@ impl PinnedDrop for WebSocket {
 mod tests {
     use super::*;
     use futures::{SinkExt, StreamExt};
+    use std::time::Duration;
     use wasm_bindgen_test::*;
 
     wasm_bindgen_test_configure!(run_in_browser);
@@ -388,4 +826,164 @@ mod tests {
             Message::Text("test 2".to_string())
         );
     }
+
+    #[wasm_bindgen_test]
+    async fn heartbeat_works() {
+        let ws_echo_server_url =
+            option_env!("WS_ECHO_SERVER_URL").expect("Did you set WS_ECHO_SERVER_URL?");
+        let ping = || Message::Text("ping".to_string());
+
+        // the echo-server answers each ping with the same message, which is taken as the pong
+        let mut ws = WebSocket::open(ws_echo_server_url).unwrap();
+        let _ = ws.next().await;
+        ws.enable_heartbeat(Heartbeat::new(
+            Duration::from_millis(20),
+            Duration::from_secs(1),
+            ping(),
+            move |msg| *msg == ping(),
+        ));
+        gloo_timers::future::sleep(Duration::from_millis(200)).await;
+        ws.send(Message::Text("test".to_string())).await.unwrap();
+        assert_eq!(
+            ws.next().await.unwrap().unwrap(),
+            Message::Text("test".to_string())
+        );
+
+        // no pong ever matches
+        ws.enable_heartbeat(Heartbeat::new(
+            Duration::from_millis(20),
+            Duration::from_millis(20),
+            Message::Text("ping".to_string()),
+            |_| false,
+        ));
+        loop {
+            match ws.next().await {
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => {
+                    assert!(matches!(e, WebSocketError::HeartbeatTimeout));
+                    break;
+                }
+                None => panic!("stream ended without a heartbeat timeout"),
+            }
+        }
+    }
+
+    #[wasm_bindgen_test]
+    async fn high_water_mark_works() {
+        let ws_echo_server_url =
+            option_env!("WS_ECHO_SERVER_URL").expect("Did you set WS_ECHO_SERVER_URL?");
+
+        let mut ws = WebSocket::open(ws_echo_server_url).unwrap();
+        ws.set_high_water_mark(Some(64 * 1024));
+
+        let chunk = vec![0u8; 16 * 1024];
+        for _ in 0..64 {
+            ws.send(Message::Bytes(chunk.clone())).await.unwrap();
+            assert!(ws.buffered_amount() <= 64 * 1024 + chunk.len() as u32);
+        }
+    }
+
+    #[wasm_bindgen_test]
+    async fn close_gracefully_works() {
+        let ws_echo_server_url =
+            option_env!("WS_ECHO_SERVER_URL").expect("Did you set WS_ECHO_SERVER_URL?");
+
+        let ws = WebSocket::open(ws_echo_server_url).unwrap();
+        let close_handle = ws.close_handle();
+        let (mut sender, _receiver) = ws.split();
+        sender
+            .send(Message::Text("test".to_string()))
+            .await
+            .unwrap();
+
+        let event = close_handle
+            .clone()
+            .close_gracefully(Some(1000), Some("done"), Duration::from_secs(5))
+            .await
+            .unwrap()
+            .expect("the close handshake timed out");
+        assert_eq!(event.code, 1000);
+        assert!(event.was_clean);
+
+        // already closed
+        let again = close_handle
+            .close_gracefully(None, None, Duration::from_millis(1))
+            .await
+            .unwrap();
+        assert_eq!(again.unwrap().code, 1000);
+    }
+
+    #[wasm_bindgen_test]
+    async fn close_gracefully_outlives_the_websocket() {
+        let ws_echo_server_url =
+            option_env!("WS_ECHO_SERVER_URL").expect("Did you set WS_ECHO_SERVER_URL?");
+
+        let mut ws = WebSocket::open(ws_echo_server_url).unwrap();
+        let _ = ws.next().await;
+        let close_handle = ws.close_handle();
+
+        // the handshake can't complete this fast
+        let timed_out = close_handle
+            .close_gracefully(Some(1000), None, Duration::ZERO)
+            .await
+            .unwrap();
+        assert!(timed_out.is_none());
+        assert!(close_handle.closed.wakers.borrow().is_empty());
+
+        drop(ws);
+        let event = close_handle
+            .close_gracefully(None, None, Duration::from_secs(5))
+            .await
+            .unwrap()
+            .expect("the close handshake timed out");
+        assert_ne!(event.reason, "client dropped");
+        assert!(event.was_clean);
+    }
+
+    #[wasm_bindgen_test]
+    async fn state_changes_works() {
+        let ws_echo_server_url =
+            option_env!("WS_ECHO_SERVER_URL").expect("Did you set WS_ECHO_SERVER_URL?");
+
+        let ws = WebSocket::open(ws_echo_server_url).unwrap();
+        let mut state_changes = ws.state_changes();
+        assert_eq!(
+            state_changes.next().await.unwrap().state(),
+            State::Connecting
+        );
+        assert_eq!(state_changes.next().await.unwrap().state(), State::Open);
+
+        ws.close_handle().close(Some(1000), Some("bye")).unwrap();
+        assert_eq!(state_changes.next().await.unwrap().state(), State::Closing);
+        match state_changes.next().await {
+            Some(StateChange::Closed(event)) => assert_eq!(event.code, 1000),
+            other => panic!("unexpected {other:?}"),
+        }
+        assert!(state_changes.next().await.is_none());
+    }
+
+    #[wasm_bindgen_test]
+    async fn blobs_are_reported_as_errors() {
+        let ws_echo_server_url =
+            option_env!("WS_ECHO_SERVER_URL").expect("Did you set WS_ECHO_SERVER_URL?");
+
+        let raw = web_sys::WebSocket::new(ws_echo_server_url).unwrap();
+        let mut ws = WebSocket::try_from(raw.clone()).unwrap();
+        let _ = ws.next().await;
+
+        raw.set_binary_type(BinaryType::Blob);
+        ws.send(Message::Bytes(vec![1, 2, 3])).await.unwrap();
+        assert!(matches!(
+            ws.next().await,
+            Some(Err(WebSocketError::UnexpectedBlob))
+        ));
+
+        ws.send(Message::Text("still open".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(
+            ws.next().await.unwrap().unwrap(),
+            Message::Text("still open".to_string())
+        );
+    }
 }
@@ -0,0 +1,118 @@
+use crate::timer::timeout;
+use crate::websocket::Message;
+use std::cell::Cell;
+use std::fmt;
+use std::rc::Rc;
+use std::time::Duration;
+
+/// Keepalive configuration for detecting dead WebSocket connections.
+///
+/// A connection can be lost without the browser noticing, e.g. when a mobile device changes
+/// networks, leaving the socket `Open` for minutes. With a heartbeat enabled, the socket sends an
+/// application-level ping message every `interval` and expects the server to answer with a pong
+/// within `timeout`. If no pong arrives, the socket is closed and its stream yields
+/// [`WebSocketError::HeartbeatTimeout`](crate::websocket::WebSocketError::HeartbeatTimeout).
+///
+/// Pongs are recognized by the given predicate and are not yielded by the stream.
+///
+/// See [`WebSocket::enable_heartbeat`](crate::websocket::futures::WebSocket::enable_heartbeat).
+///
+/// # Example
+///
+/// ```rust
+/// use gloo_net::websocket::{Heartbeat, Message};
+/// use std::time::Duration;
+///
+/// let heartbeat = Heartbeat::new(
+///     Duration::from_secs(30),
+///     Duration::from_secs(10),
+///     Message::Text(r#"{"type":"ping"}"#.to_string()),
+///     |msg| matches!(msg, Message::Text(text) if text == r#"{"type":"pong"}"#),
+/// );
+/// ```
+#[derive(Clone)]
+pub struct Heartbeat {
+    pub(crate) interval: Duration,
+    pub(crate) timeout: Duration,
+    pub(crate) ping: Message,
+    pub(crate) is_pong: Rc<dyn Fn(&Message) -> bool>,
+}
+
+impl Heartbeat {
+    /// Send `ping` every `interval`, and expect a message matching `is_pong` within `timeout`.
+    pub fn new<F>(interval: Duration, timeout: Duration, ping: Message, is_pong: F) -> Self
+    where
+        F: Fn(&Message) -> bool + 'static,
+    {
+        Self {
+            interval,
+            timeout,
+            ping,
+            is_pong: Rc::new(is_pong),
+        }
+    }
+}
+
+impl fmt::Debug for Heartbeat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Heartbeat")
+            .field("interval", &self.interval)
+            .field("timeout", &self.timeout)
+            .field("ping", &self.ping)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The heartbeat of a socket, shared by its message callback and its timer task.
+pub(crate) struct HeartbeatState {
+    pub(crate) config: Heartbeat,
+    awaiting_pong: Cell<bool>,
+}
+
+impl HeartbeatState {
+    pub(crate) fn new(config: Heartbeat) -> Self {
+        Self {
+            config,
+            awaiting_pong: Cell::new(false),
+        }
+    }
+
+    /// Records the message if it is a pong, returning whether it was one.
+    pub(crate) fn receive(&self, message: &Message) -> bool {
+        let is_pong = (self.config.is_pong)(message);
+        if is_pong {
+            self.awaiting_pong.set(false);
+        }
+        is_pong
+    }
+
+    /// Sends pings with `send_ping` until `is_alive` returns `false`, or no pong arrives in time.
+    ///
+    /// Returns `true` if the heartbeat timed out.
+    pub(crate) async fn run(
+        &self,
+        mut is_alive: impl FnMut() -> bool,
+        mut send_ping: impl FnMut(&Message),
+    ) -> bool {
+        loop {
+            sleep(self.config.interval).await;
+            if !is_alive() {
+                return false;
+            }
+            self.awaiting_pong.set(true);
+            send_ping(&self.config.ping);
+
+            sleep(self.config.timeout).await;
+            if !is_alive() {
+                return false;
+            }
+            if self.awaiting_pong.get() {
+                return true;
+            }
+        }
+    }
+}
+
+async fn sleep(duration: Duration) {
+    timeout(duration).await;
+}
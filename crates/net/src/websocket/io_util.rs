@@ -4,19 +4,18 @@ use core::task::{Context, Poll};
 use std::io;
 
 use futures_core::{ready, Stream as _};
-use futures_io::{AsyncRead, AsyncWrite};
 use futures_sink::Sink;
 
 use crate::websocket::futures::WebSocket;
 use crate::websocket::{Message as WebSocketMessage, WebSocketError};
 
 impl WebSocket {
-    /// Returns whether there are pending bytes left after calling [`AsyncRead::poll_read`] on this WebSocket.
+    /// Returns whether there are pending bytes left after calling `poll_read` on this WebSocket.
     ///
-    /// When calling [`AsyncRead::poll_read`], [`Stream::poll_next`](futures_core::Stream::poll_next) is called
+    /// When calling `poll_read`, [`Stream::poll_next`](futures_core::Stream::poll_next) is called
     /// under the hood, and when the received item is too big to fit into the provided buffer, leftover bytes are
-    /// stored. These leftover bytes are returned by subsequent calls to [`AsyncRead::poll_read`].
-    #[cfg_attr(docsrs, doc(cfg(feature = "io-util")))]
+    /// stored. These leftover bytes are returned by subsequent calls to `poll_read`.
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "io-util", feature = "tokio-io"))))]
     pub fn has_pending_bytes(&self) -> bool {
         self.read_pending_bytes.is_some()
     }
@@ -35,87 +34,158 @@ macro_rules! try_in_poll_io {
     }};
 }
 
+/// Reads the pending bytes, or the next message, with `copy`, which copies as many bytes as fit
+/// into the caller's buffer and returns how many it copied. The rest is kept for the next read.
+fn poll_read_with(
+    mut ws: Pin<&mut WebSocket>,
+    cx: &mut Context<'_>,
+    copy: impl FnOnce(&[u8]) -> usize,
+) -> Poll<io::Result<usize>> {
+    let mut data = if let Some(data) = ws.as_mut().get_mut().read_pending_bytes.take() {
+        data
+    } else {
+        match ready!(ws.as_mut().poll_next(cx)) {
+            Some(item) => match try_in_poll_io!(item) {
+                WebSocketMessage::Text(s) => s.into_bytes(),
+                WebSocketMessage::Bytes(data) => data,
+            },
+            None => return Poll::Ready(Ok(0)),
+        }
+    };
+
+    let bytes_copied = copy(&data);
+
+    if data.len() > bytes_copied {
+        data.drain(..bytes_copied);
+        ws.get_mut().read_pending_bytes = Some(data);
+    }
+
+    Poll::Ready(Ok(bytes_copied))
+}
+
+fn poll_write(
+    mut ws: Pin<&mut WebSocket>,
+    cx: &mut Context<'_>,
+    buf: &[u8],
+) -> Poll<io::Result<usize>> {
+    // try flushing preemptively
+    let _ = Sink::poll_flush(ws.as_mut(), cx);
+
+    // make sure sink is ready to send
+    try_in_poll_io!(ready!(ws.as_mut().poll_ready(cx)));
+
+    // actually submit new item
+    try_in_poll_io!(ws.start_send(WebSocketMessage::Bytes(buf.to_vec())));
+    // ^ if no error occurred, message is accepted and queued when calling `start_send`
+    // (i.e.: `to_vec` is called only once)
+
+    Poll::Ready(Ok(buf.len()))
+}
+
+fn poll_flush(ws: Pin<&mut WebSocket>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+    let res = ready!(Sink::poll_flush(ws, cx));
+    Poll::Ready(ws_result_to_io_result(res))
+}
+
+fn poll_close(ws: Pin<&mut WebSocket>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+    let res = ready!(Sink::poll_close(ws, cx));
+    Poll::Ready(ws_result_to_io_result(res))
+}
+
+fn ws_result_to_io_result(res: Result<(), WebSocketError>) -> io::Result<()> {
+    match res {
+        Ok(()) => Ok(()),
+        Err(WebSocketError::ConnectionClose(_)) => Ok(()),
+        Err(e) => Err(io::Error::new(io::ErrorKind::Other, e)),
+    }
+}
+
+#[cfg(feature = "io-util")]
 #[cfg_attr(docsrs, doc(cfg(feature = "io-util")))]
-impl AsyncRead for WebSocket {
+impl futures_io::AsyncRead for WebSocket {
     fn poll_read(
-        mut self: Pin<&mut Self>,
+        self: Pin<&mut Self>,
         cx: &mut Context<'_>,
         buf: &mut [u8],
     ) -> Poll<io::Result<usize>> {
-        let mut data = if let Some(data) = self.as_mut().get_mut().read_pending_bytes.take() {
-            data
-        } else {
-            match ready!(self.as_mut().poll_next(cx)) {
-                Some(item) => match try_in_poll_io!(item) {
-                    WebSocketMessage::Text(s) => s.into_bytes(),
-                    WebSocketMessage::Bytes(data) => data,
-                },
-                None => return Poll::Ready(Ok(0)),
-            }
-        };
-
-        let bytes_to_copy = cmp::min(buf.len(), data.len());
-        buf[..bytes_to_copy].copy_from_slice(&data[..bytes_to_copy]);
-
-        if data.len() > bytes_to_copy {
-            data.drain(..bytes_to_copy);
-            self.get_mut().read_pending_bytes = Some(data);
-        }
-
-        Poll::Ready(Ok(bytes_to_copy))
+        poll_read_with(self, cx, |data| {
+            let bytes_to_copy = cmp::min(buf.len(), data.len());
+            buf[..bytes_to_copy].copy_from_slice(&data[..bytes_to_copy]);
+            bytes_to_copy
+        })
     }
 }
 
+#[cfg(feature = "io-util")]
 #[cfg_attr(docsrs, doc(cfg(feature = "io-util")))]
-impl AsyncWrite for WebSocket {
+impl futures_io::AsyncWrite for WebSocket {
     fn poll_write(
-        mut self: Pin<&mut Self>,
+        self: Pin<&mut Self>,
         cx: &mut Context<'_>,
         buf: &[u8],
     ) -> Poll<io::Result<usize>> {
-        // try flushing preemptively
-        let _ = AsyncWrite::poll_flush(self.as_mut(), cx);
-
-        // make sure sink is ready to send
-        try_in_poll_io!(ready!(self.as_mut().poll_ready(cx)));
-
-        // actually submit new item
-        try_in_poll_io!(self.start_send(WebSocketMessage::Bytes(buf.to_vec())));
-        // ^ if no error occurred, message is accepted and queued when calling `start_send`
-        // (i.e.: `to_vec` is called only once)
-
-        Poll::Ready(Ok(buf.len()))
+        poll_write(self, cx, buf)
     }
 
     fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
-        let res = ready!(Sink::poll_flush(self, cx));
-        Poll::Ready(ws_result_to_io_result(res))
+        poll_flush(self, cx)
     }
 
     fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
-        let res = ready!(Sink::poll_close(self, cx));
-        Poll::Ready(ws_result_to_io_result(res))
+        poll_close(self, cx)
     }
 }
 
-fn ws_result_to_io_result(res: Result<(), WebSocketError>) -> io::Result<()> {
-    match res {
-        Ok(()) => Ok(()),
-        Err(WebSocketError::ConnectionClose(_)) => Ok(()),
-        Err(e) => Err(io::Error::new(io::ErrorKind::Other, e)),
+#[cfg(feature = "tokio-io")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio-io")))]
+impl tokio::io::AsyncRead for WebSocket {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let read = ready!(poll_read_with(self, cx, |data| {
+            let bytes_to_copy = cmp::min(buf.remaining(), data.len());
+            buf.put_slice(&data[..bytes_to_copy]);
+            bytes_to_copy
+        }));
+        Poll::Ready(read.map(|_| ()))
+    }
+}
+
+#[cfg(feature = "tokio-io")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio-io")))]
+impl tokio::io::AsyncWrite for WebSocket {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        poll_write(self, cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        poll_flush(self, cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        poll_close(self, cx)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use futures::{AsyncReadExt, AsyncWriteExt, StreamExt};
+    use futures::StreamExt;
     use wasm_bindgen_test::*;
 
     wasm_bindgen_test_configure!(run_in_browser);
 
+    #[cfg(feature = "io-util")]
     #[wasm_bindgen_test]
     async fn check_read_write() {
+        use futures::{AsyncReadExt, AsyncWriteExt};
+
         let ws_echo_server_url =
             option_env!("WS_ECHO_SERVER_URL").expect("Did you set WS_ECHO_SERVER_URL?");
 
@@ -137,8 +207,38 @@ mod tests {
         assert_eq!(&buf, b"test 2");
     }
 
+    #[cfg(feature = "io-util")]
     #[wasm_bindgen_test]
     async fn with_pending_bytes() {
+        use futures::{AsyncReadExt, AsyncWriteExt};
+
+        let ws_echo_server_url =
+            option_env!("WS_ECHO_SERVER_URL").expect("Did you set WS_ECHO_SERVER_URL?");
+
+        let mut ws = WebSocket::open(ws_echo_server_url).unwrap();
+
+        // ignore first message
+        // the echo-server uses it to send it's info in the first message
+        let _ = ws.next().await.unwrap();
+
+        ws.write_all(b"1234567890").await.unwrap();
+
+        let mut buf = [0u8; 5];
+
+        ws.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"12345");
+        assert!(ws.has_pending_bytes());
+
+        ws.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"67890");
+        assert!(!ws.has_pending_bytes());
+    }
+
+    #[cfg(feature = "tokio-io")]
+    #[wasm_bindgen_test]
+    async fn tokio_read_write() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
         let ws_echo_server_url =
             option_env!("WS_ECHO_SERVER_URL").expect("Did you set WS_ECHO_SERVER_URL?");
 
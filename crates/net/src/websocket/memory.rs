@@ -0,0 +1,268 @@
+//! An in-memory WebSocket connection, for testing code that works with websockets.
+//!
+//! [`pair`] creates two connected [`MemoryWebSocket`]s, a client and a server. Like
+//! [`WebSocket`](crate::websocket::futures::WebSocket), each one is a
+//! `Stream<Item = Result<Message, WebSocketError>>` and a `Sink<Message>`. So code that is
+//! generic over those traits can be tested without a browser or a server. The end of a test can
+//! also close the connection with any close code, or make it fail, to check how the code under
+//! test handles that.
+//!
+//! # Example
+//!
+//! ```rust
+//! use gloo_net::websocket::memory::pair;
+//! use gloo_net::websocket::{Message, WebSocketError};
+//! use futures::{SinkExt, StreamExt};
+//!
+//! # futures::executor::block_on(async {
+//! let (mut client, mut server) = pair();
+//!
+//! client.send(Message::Text("ping".to_string())).await.unwrap();
+//! assert_eq!(server.next().await.unwrap().unwrap(), Message::Text("ping".to_string()));
+//!
+//! server.close(Some(4000), Some("going away"));
+//! match client.next().await {
+//!     Some(Err(WebSocketError::ConnectionClose(event))) => assert_eq!(event.code, 4000),
+//!     _ => unreachable!(),
+//! }
+//! assert!(client.next().await.is_none());
+//! # })
+//! ```
+use crate::websocket::events::CloseEvent;
+use crate::websocket::{Message, State, WebSocketError};
+use futures_channel::mpsc;
+use futures_core::Stream;
+use futures_sink::Sink;
+use std::cell::Cell;
+use std::fmt;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+type Item = Result<Message, WebSocketError>;
+
+/// Create a connected pair of in-memory websockets, the client first.
+///
+/// The connection is open right away.
+pub fn pair() -> (MemoryWebSocket, MemoryWebSocket) {
+    let (client_sender, client_receiver) = mpsc::unbounded();
+    let (server_sender, server_receiver) = mpsc::unbounded();
+    let link = Rc::new(Link {
+        closed: Cell::new(false),
+        ends: [client_sender, server_sender],
+    });
+
+    (
+        MemoryWebSocket {
+            link: Rc::clone(&link),
+            peer: 1,
+            receiver: client_receiver,
+        },
+        MemoryWebSocket {
+            link,
+            peer: 0,
+            receiver: server_receiver,
+        },
+    )
+}
+
+/// The connection between the two ends of a [`pair`].
+struct Link {
+    closed: Cell<bool>,
+    ends: [mpsc::UnboundedSender<Item>; 2],
+}
+
+impl Link {
+    /// Sends the errors to both ends and ends their streams, unless the connection is already
+    /// closed.
+    fn close(&self, errors: impl Fn() -> Vec<WebSocketError>) {
+        if self.closed.replace(true) {
+            return;
+        }
+        for end in &self.ends {
+            for error in errors() {
+                let _ = end.unbounded_send(Err(error));
+            }
+            end.close_channel();
+        }
+    }
+}
+
+/// One end of an in-memory websocket connection, created by [`pair`].
+///
+/// Messages sent into it are received by the other end, in order. Once the connection is
+/// closed, messages sent are discarded, as a browser does. Dropping either end closes the
+/// connection, as [`close`](Self::close) without a code would.
+pub struct MemoryWebSocket {
+    link: Rc<Link>,
+    peer: usize,
+    receiver: mpsc::UnboundedReceiver<Item>,
+}
+
+impl MemoryWebSocket {
+    /// Closes the connection.
+    ///
+    /// Both ends receive a [`WebSocketError::ConnectionClose`] with the code and reason, then
+    /// their streams end. Without a code, the close event has the code 1005, for no status, as
+    /// in a browser.
+    pub fn close(&self, code: Option<u16>, reason: Option<&str>) {
+        let event = CloseEvent {
+            code: code.unwrap_or(1005),
+            reason: reason.unwrap_or_default().to_string(),
+            was_clean: true,
+        };
+        self.link
+            .close(|| vec![WebSocketError::ConnectionClose(event.clone())]);
+    }
+
+    /// Simulates the loss of the connection.
+    ///
+    /// Both ends receive a [`WebSocketError::ConnectionError`], then a
+    /// [`WebSocketError::ConnectionClose`] with the code 1006, for an abnormal closure, then
+    /// their streams end.
+    pub fn fail(&self) {
+        self.link.close(|| {
+            vec![
+                WebSocketError::ConnectionError,
+                WebSocketError::ConnectionClose(CloseEvent {
+                    code: 1006,
+                    reason: String::new(),
+                    was_clean: false,
+                }),
+            ]
+        });
+    }
+
+    /// The state of the connection, either [`State::Open`] or [`State::Closed`].
+    pub fn state(&self) -> State {
+        if self.link.closed.get() {
+            State::Closed
+        } else {
+            State::Open
+        }
+    }
+}
+
+impl fmt::Debug for MemoryWebSocket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemoryWebSocket")
+            .field("state", &self.state())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Stream for MemoryWebSocket {
+    type Item = Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
+
+impl Sink<Message> for MemoryWebSocket {
+    type Error = WebSocketError;
+
+    fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: Message) -> Result<(), Self::Error> {
+        if !self.link.closed.get() {
+            let _ = self.link.ends[self.peer].unbounded_send(Ok(item));
+        }
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Drop for MemoryWebSocket {
+    fn drop(&mut self) {
+        self.close(None, None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use futures::{SinkExt, StreamExt};
+
+    fn text(text: &str) -> Message {
+        Message::Text(text.to_string())
+    }
+
+    #[test]
+    fn messages_are_exchanged() {
+        block_on(async {
+            let (mut client, mut server) = pair();
+            client.send(text("1")).await.unwrap();
+            client.send(Message::Bytes(vec![2])).await.unwrap();
+            server.send(text("3")).await.unwrap();
+
+            assert_eq!(server.next().await.unwrap().unwrap(), text("1"));
+            assert_eq!(
+                server.next().await.unwrap().unwrap(),
+                Message::Bytes(vec![2])
+            );
+            assert_eq!(client.next().await.unwrap().unwrap(), text("3"));
+        })
+    }
+
+    #[test]
+    fn close_reaches_both_ends() {
+        block_on(async {
+            let (mut client, mut server) = pair();
+            client.send(text("before")).await.unwrap();
+            client.close(Some(4001), Some("bye"));
+            client.send(text("after")).await.unwrap();
+            assert!(matches!(server.state(), State::Closed));
+
+            assert_eq!(server.next().await.unwrap().unwrap(), text("before"));
+            for end in [&mut server, &mut client] {
+                match end.next().await {
+                    Some(Err(WebSocketError::ConnectionClose(event))) => {
+                        assert_eq!((event.code, event.reason.as_str()), (4001, "bye"));
+                        assert!(event.was_clean);
+                    }
+                    other => panic!("unexpected {other:?}"),
+                }
+                assert!(end.next().await.is_none());
+            }
+        })
+    }
+
+    #[test]
+    fn failure_and_drop() {
+        block_on(async {
+            let (mut client, server) = pair();
+            server.fail();
+            server.close(Some(1000), None);
+            assert!(matches!(
+                client.next().await,
+                Some(Err(WebSocketError::ConnectionError))
+            ));
+            match client.next().await {
+                Some(Err(WebSocketError::ConnectionClose(event))) => {
+                    assert_eq!(event.code, 1006);
+                    assert!(!event.was_clean);
+                }
+                other => panic!("unexpected {other:?}"),
+            }
+            assert!(client.next().await.is_none());
+
+            let (mut client, server) = pair();
+            drop(server);
+            match client.next().await {
+                Some(Err(WebSocketError::ConnectionClose(event))) => assert_eq!(event.code, 1005),
+                other => panic!("unexpected {other:?}"),
+            }
+        })
+    }
+}
@@ -2,13 +2,42 @@
 //!
 //! This API is provided in the following flavors:
 //! - [Futures API][futures]
+//! - [Reconnecting WebSocket][reconnecting], which reopens the connection when it is lost
+//! - [Blob WebSocket][blob], which receives binary messages as blobs
+//! - [Typed WebSocket][typed], which encodes and decodes messages with a codec
+//! - [RPC client][rpc], which matches responses to requests, e.g. with JSON-RPC 2.0
+//! - [In-memory WebSocket][memory], a connected pair for tests
+//! - [Multiplexer][multiplex], which shares a connection between subscribers to topics
+//! - [STOMP client][stomp], for STOMP 1.2 message brokers
 
+#[cfg(feature = "blob")]
+#[cfg_attr(docsrs, doc(cfg(feature = "blob")))]
+pub mod blob;
+mod driver;
 pub mod events;
 pub mod futures;
+mod heartbeat;
+#[cfg(all(feature = "websocket", feature = "mock"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "websocket", feature = "mock"))))]
+pub mod memory;
+pub mod multiplex;
+pub mod reconnecting;
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+pub mod rpc;
+pub mod stomp;
+#[cfg(any(feature = "json", feature = "cbor", feature = "msgpack"))]
+#[cfg_attr(
+    docsrs,
+    doc(cfg(any(feature = "json", feature = "cbor", feature = "msgpack")))
+)]
+pub mod typed;
 
-#[cfg(feature = "io-util")]
+#[cfg(any(feature = "io-util", feature = "tokio-io"))]
 mod io_util;
 
+pub use heartbeat::Heartbeat;
+
 use events::CloseEvent;
 use gloo_utils::errors::JsError;
 use std::fmt;
@@ -26,7 +55,7 @@ pub enum Message {
 ///
 /// See [`WebSocket.readyState` on MDN](https://developer.mozilla.org/en-US/docs/Web/API/WebSocket/readyState)
 /// to learn more.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum State {
     /// The connection has not yet been established.
     Connecting,
@@ -39,6 +68,35 @@ pub enum State {
     Closed,
 }
 
+/// A transition of the state of a websocket, yielded by
+/// [`WebSocket::state_changes`](futures::WebSocket::state_changes).
+///
+/// It mirrors [`State`], with the `close` event attached to `Closed`.
+#[derive(Clone, Debug)]
+pub enum StateChange {
+    /// The connection has not yet been established.
+    Connecting,
+    /// The WebSocket connection is established and communication is possible.
+    Open,
+    /// The closing handshake was started by this side.
+    Closing,
+    /// The connection has been closed or could not be opened, with the code and reason of the
+    /// `close` event.
+    Closed(CloseEvent),
+}
+
+impl StateChange {
+    /// The state without the `close` event.
+    pub fn state(&self) -> State {
+        match self {
+            StateChange::Connecting => State::Connecting,
+            StateChange::Open => State::Open,
+            StateChange::Closing => State::Closing,
+            StateChange::Closed(_) => State::Closed,
+        }
+    }
+}
+
 /// Error returned by WebSocket
 #[derive(Debug)]
 #[non_exhaustive]
@@ -49,6 +107,15 @@ pub enum WebSocketError {
     ConnectionClose(CloseEvent),
     /// Message failed to send.
     MessageSendError(JsError),
+    /// No pong was received in time for a ping sent by the [`Heartbeat`]. The connection is
+    /// closed.
+    HeartbeatTimeout,
+    /// A message could not be encoded or decoded by the codec of a
+    /// [`TypedWebSocket`](typed::TypedWebSocket).
+    CodecError(crate::Error),
+    /// A binary message was received as a `Blob`, because the `binaryType` of the underlying
+    /// socket was set to `"blob"`. Only a `BlobWebSocket` receives blobs.
+    UnexpectedBlob,
 }
 
 impl fmt::Display for WebSocketError {
@@ -61,6 +128,14 @@ impl fmt::Display for WebSocketError {
                 e.code, e.reason
             ),
             WebSocketError::MessageSendError(e) => write!(f, "{e}"),
+            WebSocketError::HeartbeatTimeout => write!(f, "WebSocket heartbeat timed out"),
+            WebSocketError::CodecError(e) => write!(f, "{e}"),
+            WebSocketError::UnexpectedBlob => {
+                write!(
+                    f,
+                    "WebSocket received a Blob, set binaryType to \"arraybuffer\""
+                )
+            }
         }
     }
 }
@@ -0,0 +1,427 @@
+//! Topic based subscriptions over a single [`WebSocket`](crate::websocket::futures::WebSocket).
+//!
+//! A [`Multiplexer`] shares one connection between independent consumers. Each consumer
+//! [subscribes](Multiplexer::subscribe) to a topic and gets a [`Subscription`] stream of the
+//! messages for that topic. The topic of an incoming message is found by a function given to the
+//! multiplexer, e.g. by reading a field of a JSON message.
+//!
+//! When the first subscriber to a topic appears, a subscribe message is sent to the server, and
+//! when the last one is dropped, an unsubscribe message, so the server only sends what is needed.
+//!
+//! # Example
+//!
+//! ```rust
+//! use gloo_net::websocket::futures::WebSocket;
+//! use gloo_net::websocket::multiplex::Multiplexer;
+//! use gloo_net::websocket::Message;
+//! use wasm_bindgen_futures::spawn_local;
+//! use futures::StreamExt;
+//!
+//! # macro_rules! console_log {
+//! #    ($($expr:expr),*) => {{}};
+//! # }
+//! # fn no_run() {
+//! // messages look like `prices:{"symbol":"ABC","value":1}`
+//! let ws = WebSocket::open("wss://api.example.com/feeds").unwrap();
+//! let mux = Multiplexer::builder(|msg| match msg {
+//!     Message::Text(text) => text.split_once(':').map(|(topic, _)| topic.to_string()),
+//!     _ => None,
+//! })
+//! .subscribe_message(|topic| Message::Text(format!("subscribe:{}", topic)))
+//! .unsubscribe_message(|topic| Message::Text(format!("unsubscribe:{}", topic)))
+//! .build(ws);
+//!
+//! let mut prices = mux.subscribe("prices");
+//! spawn_local(async move {
+//!     while let Some(msg) = prices.next().await {
+//!         console_log!(format!("{:?}", msg))
+//!     }
+//! })
+//! # }
+//! ```
+use crate::websocket::driver::{next_step, send, Step};
+use crate::websocket::{Message, WebSocketError};
+use futures_channel::mpsc;
+use futures_core::Stream;
+use futures_sink::Sink;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+use wasm_bindgen_futures::spawn_local;
+
+type TopicOf = Box<dyn Fn(&Message) -> Option<String>>;
+type TopicMessage = Rc<dyn Fn(&str) -> Message>;
+
+/// Configuration for a [`Multiplexer`], created by [`Multiplexer::builder`].
+pub struct MultiplexerBuilder {
+    topic_of: TopicOf,
+    subscribe_message: Option<TopicMessage>,
+    unsubscribe_message: Option<TopicMessage>,
+}
+
+impl MultiplexerBuilder {
+    /// Sets the message sent when the first subscriber to a topic appears.
+    pub fn subscribe_message<F>(mut self, message: F) -> Self
+    where
+        F: Fn(&str) -> Message + 'static,
+    {
+        self.subscribe_message = Some(Rc::new(message));
+        self
+    }
+
+    /// Sets the message sent when the last subscriber to a topic is dropped.
+    pub fn unsubscribe_message<F>(mut self, message: F) -> Self
+    where
+        F: Fn(&str) -> Message + 'static,
+    {
+        self.unsubscribe_message = Some(Rc::new(message));
+        self
+    }
+
+    /// Start routing the messages received by `ws`.
+    ///
+    /// `ws` is usually a [`WebSocket`](crate::websocket::futures::WebSocket), but any stream and
+    /// sink of [`Message`]s can be used, such as an in-memory websocket in tests.
+    pub fn build<S>(self, ws: S) -> Multiplexer
+    where
+        S: Stream<Item = Result<Message, WebSocketError>>
+            + Sink<Message, Error = WebSocketError>
+            + Unpin
+            + 'static,
+    {
+        let topics = Rc::new(RefCell::new(Topics::default()));
+        let (outbound, outbound_receiver) = mpsc::unbounded();
+
+        spawn_local(run(
+            ws,
+            self.topic_of,
+            Rc::clone(&topics),
+            outbound_receiver,
+        ));
+
+        Multiplexer {
+            shared: Rc::new(Shared {
+                topics,
+                outbound,
+                subscribe_message: self.subscribe_message,
+                unsubscribe_message: self.unsubscribe_message,
+            }),
+        }
+    }
+}
+
+impl fmt::Debug for MultiplexerBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MultiplexerBuilder")
+            .field("subscribe_message", &self.subscribe_message.is_some())
+            .field("unsubscribe_message", &self.unsubscribe_message.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The subscribers of each topic.
+#[derive(Default)]
+struct Topics {
+    next_id: u64,
+    closed: bool,
+    subscribers: HashMap<String, Vec<(u64, mpsc::UnboundedSender<Message>)>>,
+}
+
+impl Topics {
+    /// Adds a subscriber, returning its ID and whether it is the first one of the topic.
+    fn add(&mut self, topic: &str, sender: mpsc::UnboundedSender<Message>) -> (u64, bool) {
+        let id = self.next_id;
+        self.next_id += 1;
+        if self.closed {
+            // dropping the sender ends the subscription right away
+            return (id, false);
+        }
+        let subscribers = self.subscribers.entry(topic.to_string()).or_default();
+        subscribers.push((id, sender));
+        (id, subscribers.len() == 1)
+    }
+
+    /// Removes a subscriber, returning whether it was the last one of the topic.
+    fn remove(&mut self, topic: &str, id: u64) -> bool {
+        let subscribers = match self.subscribers.get_mut(topic) {
+            Some(subscribers) => subscribers,
+            None => return false,
+        };
+        subscribers.retain(|(subscriber, _)| *subscriber != id);
+        if subscribers.is_empty() {
+            self.subscribers.remove(topic);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Ends all subscriptions, current and future.
+    fn close(&mut self) {
+        self.closed = true;
+        self.subscribers.clear();
+    }
+
+    /// Sends the message to every subscriber of the topic.
+    fn route(&self, topic: &str, message: Message) {
+        if let Some(subscribers) = self.subscribers.get(topic) {
+            for (_, sender) in subscribers {
+                let _ = sender.unbounded_send(message.clone());
+            }
+        }
+    }
+}
+
+struct Shared {
+    topics: Rc<RefCell<Topics>>,
+    outbound: mpsc::UnboundedSender<Message>,
+    subscribe_message: Option<TopicMessage>,
+    unsubscribe_message: Option<TopicMessage>,
+}
+
+/// Routes the messages of a [`WebSocket`](crate::websocket::futures::WebSocket) to
+/// subscribers by topic.
+///
+/// It can be cloned to subscribe from several places. The connection is closed once the
+/// multiplexer, its clones and all its subscriptions have been dropped.
+#[derive(Clone)]
+pub struct Multiplexer {
+    shared: Rc<Shared>,
+}
+
+impl Multiplexer {
+    /// Configure a multiplexer that finds the topic of a message with `topic_of`.
+    ///
+    /// Messages for which it returns `None`, or which no one is subscribed to, are discarded.
+    pub fn builder<F>(topic_of: F) -> MultiplexerBuilder
+    where
+        F: Fn(&Message) -> Option<String> + 'static,
+    {
+        MultiplexerBuilder {
+            topic_of: Box::new(topic_of),
+            subscribe_message: None,
+            unsubscribe_message: None,
+        }
+    }
+
+    /// Subscribe to the messages of a topic.
+    ///
+    /// If this is the first subscriber to the topic, the subscribe message is sent.
+    pub fn subscribe(&self, topic: impl Into<String>) -> Subscription {
+        let topic = topic.into();
+        let (sender, receiver) = mpsc::unbounded();
+        let (id, first) = self.shared.topics.borrow_mut().add(&topic, sender);
+        if first {
+            if let Some(message) = &self.shared.subscribe_message {
+                let _ = self.shared.outbound.unbounded_send(message(&topic));
+            }
+        }
+
+        Subscription {
+            shared: Rc::clone(&self.shared),
+            topic,
+            id,
+            receiver,
+        }
+    }
+
+    /// Send a message over the connection.
+    pub fn send(&self, message: Message) -> Result<(), WebSocketError> {
+        self.shared
+            .outbound
+            .unbounded_send(message)
+            .map_err(|_| WebSocketError::ConnectionError)
+    }
+
+    /// The topics with at least one subscriber.
+    pub fn topics(&self) -> Vec<String> {
+        self.shared
+            .topics
+            .borrow()
+            .subscribers
+            .keys()
+            .cloned()
+            .collect()
+    }
+}
+
+impl fmt::Debug for Multiplexer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Multiplexer")
+            .field("topics", &self.topics())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The messages of a topic, returned by [`Multiplexer::subscribe`].
+///
+/// The stream ends when the connection is closed. Dropping the last subscription to a topic
+/// sends the unsubscribe message.
+pub struct Subscription {
+    shared: Rc<Shared>,
+    topic: String,
+    id: u64,
+    receiver: mpsc::UnboundedReceiver<Message>,
+}
+
+impl Subscription {
+    /// The topic of this subscription.
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+}
+
+impl fmt::Debug for Subscription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Subscription")
+            .field("topic", &self.topic)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Stream for Subscription {
+    type Item = Message;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        let last = self.shared.topics.borrow_mut().remove(&self.topic, self.id);
+        if last {
+            if let Some(message) = &self.shared.unsubscribe_message {
+                let _ = self.shared.outbound.unbounded_send(message(&self.topic));
+            }
+        }
+    }
+}
+
+async fn run<S>(
+    mut ws: S,
+    topic_of: TopicOf,
+    topics: Rc<RefCell<Topics>>,
+    mut outbound: mpsc::UnboundedReceiver<Message>,
+) where
+    S: Stream<Item = Result<Message, WebSocketError>>
+        + Sink<Message, Error = WebSocketError>
+        + Unpin,
+{
+    loop {
+        match next_step(&mut ws, Some(&mut outbound)).await {
+            Step::Inbound(Some(Ok(message))) => {
+                if let Some(topic) = topic_of(&message) {
+                    topics.borrow().route(&topic, message);
+                }
+            }
+            // the stream ends after the close event
+            Step::Inbound(Some(Err(_))) => {}
+            Step::Inbound(None) => break,
+            Step::Outbound(Some(message)) => {
+                if send(&mut ws, message).await.is_err() {
+                    break;
+                }
+            }
+            // the multiplexer and all subscriptions were dropped
+            Step::Outbound(None) => return,
+        }
+    }
+    topics.borrow_mut().close();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    #[test]
+    fn topics_track_subscribers() {
+        let mut topics = Topics::default();
+        let (a_sender, mut a) = mpsc::unbounded();
+        let (b_sender, mut b) = mpsc::unbounded();
+
+        let (a_id, first) = topics.add("prices", a_sender);
+        assert!(first);
+        let (b_id, first) = topics.add("prices", b_sender);
+        assert!(!first);
+
+        topics.route("prices", Message::Text("1".to_string()));
+        topics.route("news", Message::Text("2".to_string()));
+        assert_eq!(a.try_next().unwrap(), Some(Message::Text("1".to_string())));
+        assert_eq!(b.try_next().unwrap(), Some(Message::Text("1".to_string())));
+        assert!(a.try_next().is_err());
+
+        assert!(!topics.remove("prices", a_id));
+        assert!(topics.remove("prices", b_id));
+        assert!(topics.subscribers.is_empty());
+        assert!(!topics.remove("prices", b_id));
+        assert_eq!(futures::executor::block_on(b.next()), None);
+
+        topics.close();
+        let (c_sender, mut c) = mpsc::unbounded();
+        topics.add("prices", c_sender);
+        assert_eq!(futures::executor::block_on(c.next()), None);
+    }
+
+    #[cfg(feature = "mock")]
+    #[wasm_bindgen_test::wasm_bindgen_test]
+    async fn subscriptions_over_a_memory_websocket() {
+        use crate::websocket::memory::pair;
+        use futures::SinkExt;
+
+        let text = |text: &str| Message::Text(text.to_string());
+        let (client, mut server) = pair();
+        let mux = Multiplexer::builder(|msg| match msg {
+            Message::Text(text) => text.split_once(':').map(|(topic, _)| topic.to_string()),
+            _ => None,
+        })
+        .subscribe_message(|topic| Message::Text(format!("subscribe:{topic}")))
+        .unsubscribe_message(|topic| Message::Text(format!("unsubscribe:{topic}")))
+        .build(client);
+
+        let mut a = mux.subscribe("prices");
+        let mut b = mux.subscribe("prices");
+        let news = mux.subscribe("news");
+        assert_eq!(
+            server.next().await.unwrap().unwrap(),
+            text("subscribe:prices")
+        );
+        assert_eq!(
+            server.next().await.unwrap().unwrap(),
+            text("subscribe:news")
+        );
+
+        for message in ["prices:1", "weather:2", "prices:3"] {
+            server.send(text(message)).await.unwrap();
+        }
+        for subscription in [&mut a, &mut b] {
+            assert_eq!(subscription.next().await, Some(text("prices:1")));
+            assert_eq!(subscription.next().await, Some(text("prices:3")));
+        }
+
+        drop(news);
+        drop(a);
+        drop(b);
+        let mut prices = mux.subscribe("prices");
+        assert_eq!(
+            server.next().await.unwrap().unwrap(),
+            text("unsubscribe:news")
+        );
+        assert_eq!(
+            server.next().await.unwrap().unwrap(),
+            text("unsubscribe:prices")
+        );
+        assert_eq!(
+            server.next().await.unwrap().unwrap(),
+            text("subscribe:prices")
+        );
+
+        server.close(Some(1000), None);
+        assert_eq!(prices.next().await, None);
+        assert_eq!(mux.subscribe("prices").next().await, None);
+    }
+}
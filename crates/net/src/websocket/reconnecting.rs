@@ -0,0 +1,612 @@
+//! A WebSocket that reopens its connection when it is lost.
+//!
+//! [`ReconnectingWebSocket`] keeps a [`WebSocket`] open in the background. When the connection
+//! is closed or fails, it opens a new one after a delay that grows with each failed attempt, as
+//! configured by [`Backoff`]. Messages sent while it is disconnected are queued and sent once
+//! the connection is back.
+//!
+//! The stream yields an [`Event`] for every received message, and also when a connection is
+//! established or lost, so applications can show the connection status or resynchronize.
+//!
+//! # Example
+//!
+//! ```rust
+//! use gloo_net::websocket::Message;
+//! use gloo_net::websocket::reconnecting::{Backoff, Event, ReconnectingWebSocket};
+//! use wasm_bindgen_futures::spawn_local;
+//! use futures::{SinkExt, StreamExt};
+//! use std::time::Duration;
+//!
+//! # macro_rules! console_log {
+//! #    ($($expr:expr),*) => {{}};
+//! # }
+//! # fn no_run() {
+//! let mut ws = ReconnectingWebSocket::builder("wss://echo.websocket.org")
+//!     .backoff(Backoff::new(Duration::from_millis(500), Duration::from_secs(30)))
+//!     .handshake(|ws| {
+//!         Box::pin(async move { ws.send(Message::Text("auth token".to_string())).await })
+//!     })
+//!     .build()
+//!     .unwrap();
+//!
+//! spawn_local(async move {
+//!     ws.send(Message::Text(String::from("test"))).await.unwrap();
+//!     while let Some(event) = ws.next().await {
+//!         match event {
+//!             Event::Connected => console_log!("connected"),
+//!             Event::Message(msg) => console_log!(format!("{:?}", msg)),
+//!             Event::Disconnected(e) => console_log!(format!("disconnected: {}", e)),
+//!             _ => {}
+//!         }
+//!     }
+//!     console_log!("Gave up reconnecting")
+//! })
+//! # }
+//! ```
+use crate::js_to_js_error;
+use crate::timer::timeout;
+use crate::websocket::driver::{next_step, send, Step};
+use crate::websocket::futures::WebSocket;
+use crate::websocket::{Heartbeat, Message, State, WebSocketError};
+use futures_channel::mpsc;
+use futures_core::Stream;
+use futures_sink::Sink;
+use gloo_utils::errors::JsError;
+use std::fmt;
+use std::future::{poll_fn, Future};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use wasm_bindgen_futures::spawn_local;
+
+/// The future returned by a [handshake](ReconnectingWebSocketBuilder::handshake) callback.
+pub type HandshakeFuture<'a> = Pin<Box<dyn Future<Output = Result<(), WebSocketError>> + 'a>>;
+
+type Handshake<S> = Rc<dyn for<'a> Fn(&'a mut S) -> HandshakeFuture<'a>>;
+type Open<S> = Rc<dyn Fn(&Target) -> Result<S, JsError>>;
+
+/// The delays between attempts to reconnect.
+///
+/// The first attempt after a connection is lost waits for the initial delay, and each
+/// consecutive failed attempt multiplies it by the factor, up to the maximum delay.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Backoff {
+    initial: Duration,
+    max: Duration,
+    factor: u32,
+    max_retries: Option<u32>,
+}
+
+impl Backoff {
+    /// Create a backoff starting at `initial` and doubling up to `max`, retrying forever.
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Self {
+            initial,
+            max,
+            factor: 2,
+            max_retries: None,
+        }
+    }
+
+    /// Sets the factor the delay is multiplied by after each failed attempt. Defaults to 2.
+    pub fn factor(mut self, factor: u32) -> Self {
+        self.factor = factor;
+        self
+    }
+
+    /// Gives up after the given number of consecutive failed attempts to connect.
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = Some(max_retries);
+        self
+    }
+
+    /// The delay before the attempt following `failures` consecutive failed attempts.
+    pub fn delay(&self, failures: u32) -> Duration {
+        self.factor
+            .checked_pow(failures)
+            .and_then(|factor| self.initial.checked_mul(factor))
+            .map_or(self.max, |delay| delay.min(self.max))
+    }
+}
+
+impl Default for Backoff {
+    /// Starts at one second and doubles up to 30 seconds, retrying forever.
+    fn default() -> Self {
+        Self::new(Duration::from_secs(1), Duration::from_secs(30))
+    }
+}
+
+/// An event of a [`ReconnectingWebSocket`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Event {
+    /// A connection was established, and the handshake, if any, completed.
+    Connected,
+    /// A message was received.
+    Message(Message),
+    /// The connection was lost. Holds the error that ended it, usually
+    /// [`WebSocketError::ConnectionClose`].
+    Disconnected(WebSocketError),
+}
+
+/// Configuration for a [`ReconnectingWebSocket`], created by [`ReconnectingWebSocket::builder`]
+/// or [`ReconnectingWebSocket::builder_with`].
+pub struct ReconnectingWebSocketBuilder<S = WebSocket> {
+    target: Target,
+    open: Open<S>,
+    /// Whether messages can be sent, which only a [`WebSocket`] can tell.
+    is_open: fn(&S) -> bool,
+    backoff: Backoff,
+    handshake: Option<Handshake<S>>,
+}
+
+/// Where a [`WebSocket`] connects to.
+#[derive(Debug, Default)]
+struct Target {
+    url: String,
+    protocols: Vec<String>,
+    heartbeat: Option<Heartbeat>,
+}
+
+impl ReconnectingWebSocketBuilder {
+    /// Adds a sub-protocol to request from the server.
+    pub fn protocol(mut self, protocol: impl Into<String>) -> Self {
+        self.target.protocols.push(protocol.into());
+        self
+    }
+
+    /// Enables a [`Heartbeat`] on each connection, so that dead connections are detected and
+    /// replaced.
+    pub fn heartbeat(mut self, heartbeat: Heartbeat) -> Self {
+        self.target.heartbeat = Some(heartbeat);
+        self
+    }
+}
+
+impl<S> ReconnectingWebSocketBuilder<S>
+where
+    S: Stream<Item = Result<Message, WebSocketError>>
+        + Sink<Message, Error = WebSocketError>
+        + Unpin
+        + 'static,
+{
+    /// Sets the delays between attempts to reconnect. Defaults to [`Backoff::default`].
+    pub fn backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Sets a callback run on each new connection, before [`Event::Connected`] is emitted and
+    /// before any queued message is sent.
+    ///
+    /// It is given the open [`WebSocket`], so it can authenticate, subscribe again, and so on.
+    /// Messages it does not read from the socket are yielded as usual afterwards. If it returns
+    /// an error, the connection is dropped and counts as a failed attempt.
+    pub fn handshake<F>(mut self, handshake: F) -> Self
+    where
+        F: for<'a> Fn(&'a mut S) -> HandshakeFuture<'a> + 'static,
+    {
+        self.handshake = Some(Rc::new(handshake));
+        self
+    }
+
+    /// Open the first connection and start reconnecting in the background.
+    ///
+    /// This fails in the same cases as [`WebSocket::open`], e.g. if the URL is invalid, or as
+    /// the function given to [`ReconnectingWebSocket::builder_with`].
+    pub fn build(self) -> Result<ReconnectingWebSocket, JsError> {
+        let ws = (self.open)(&self.target)?;
+        let (outbound, outbound_receiver) = mpsc::unbounded();
+        let (event_sender, events) = mpsc::unbounded();
+
+        spawn_local(run(self, ws, outbound_receiver, event_sender));
+
+        Ok(ReconnectingWebSocket { outbound, events })
+    }
+}
+
+fn open(target: &Target) -> Result<WebSocket, JsError> {
+    let ws = if target.protocols.is_empty() {
+        web_sys::WebSocket::new(&target.url)
+    } else {
+        let protocols: js_sys::Array = target
+            .protocols
+            .iter()
+            .map(|protocol| wasm_bindgen::JsValue::from_str(protocol))
+            .collect();
+        web_sys::WebSocket::new_with_str_sequence(&target.url, &protocols)
+    };
+    let mut ws = WebSocket::try_from(ws.map_err(js_to_js_error)?)?;
+    if let Some(heartbeat) = &target.heartbeat {
+        ws.enable_heartbeat(heartbeat.clone());
+    }
+    Ok(ws)
+}
+
+impl<S> fmt::Debug for ReconnectingWebSocketBuilder<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReconnectingWebSocketBuilder")
+            .field("url", &self.target.url)
+            .field("protocols", &self.target.protocols)
+            .field("backoff", &self.backoff)
+            .field("handshake", &self.handshake.is_some())
+            .field("heartbeat", &self.target.heartbeat)
+            .finish()
+    }
+}
+
+/// A WebSocket connection that is reopened when it is lost.
+///
+/// This is a [`Stream`] of [`Event`]s and a [`Sink`] of [`Message`]s. Sending only queues the
+/// message, so it succeeds while disconnected. The stream ends when the [`Backoff`] gives up
+/// reconnecting. Dropping it closes the connection.
+pub struct ReconnectingWebSocket {
+    outbound: mpsc::UnboundedSender<Message>,
+    events: mpsc::UnboundedReceiver<Event>,
+}
+
+impl ReconnectingWebSocket {
+    /// Configure a reconnecting WebSocket for the given url.
+    pub fn builder(url: impl Into<String>) -> ReconnectingWebSocketBuilder {
+        ReconnectingWebSocketBuilder {
+            target: Target {
+                url: url.into(),
+                ..Target::default()
+            },
+            open: Rc::new(open),
+            is_open: |ws| matches!(ws.state(), State::Open),
+            backoff: Backoff::default(),
+            handshake: None,
+        }
+    }
+
+    /// Configure a reconnecting connection opened by `open`, e.g. with
+    /// [`memory::pair`](crate::websocket::memory::pair) in tests.
+    ///
+    /// Any stream and sink of [`Message`]s can be used. Errors of `open` end the
+    /// `ReconnectingWebSocket`, and connections that close or fail are replaced.
+    pub fn builder_with<S, F>(open: F) -> ReconnectingWebSocketBuilder<S>
+    where
+        S: Stream<Item = Result<Message, WebSocketError>>
+            + Sink<Message, Error = WebSocketError>
+            + Unpin
+            + 'static,
+        F: Fn() -> Result<S, JsError> + 'static,
+    {
+        ReconnectingWebSocketBuilder {
+            target: Target::default(),
+            open: Rc::new(move |_| open()),
+            is_open: |_| true,
+            backoff: Backoff::default(),
+            handshake: None,
+        }
+    }
+
+    /// Closes the connection and stops reconnecting.
+    pub fn close(self) {}
+}
+
+impl fmt::Debug for ReconnectingWebSocket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReconnectingWebSocket")
+            .field("closed", &self.outbound.is_closed())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Stream for ReconnectingWebSocket {
+    type Item = Event;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.events).poll_next(cx)
+    }
+}
+
+impl Sink<Message> for ReconnectingWebSocket {
+    type Error = WebSocketError;
+
+    fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        if self.outbound.is_closed() {
+            Poll::Ready(Err(WebSocketError::ConnectionError))
+        } else {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: Message) -> Result<(), Self::Error> {
+        self.outbound
+            .unbounded_send(item)
+            .map_err(|_| WebSocketError::ConnectionError)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+async fn run<S>(
+    config: ReconnectingWebSocketBuilder<S>,
+    mut ws: S,
+    mut outbound: mpsc::UnboundedReceiver<Message>,
+    events: mpsc::UnboundedSender<Event>,
+) where
+    S: Stream<Item = Result<Message, WebSocketError>>
+        + Sink<Message, Error = WebSocketError>
+        + Unpin,
+{
+    // a message taken from the queue that could not be sent yet
+    let mut pending = None;
+    let mut failures = 0;
+    loop {
+        if connect(&mut ws, config.is_open, config.handshake.as_ref())
+            .await
+            .is_ok()
+        {
+            failures = 0;
+            if events.unbounded_send(Event::Connected).is_err() {
+                return;
+            }
+            match serve(
+                &mut ws,
+                config.is_open,
+                &mut outbound,
+                &mut pending,
+                &events,
+            )
+            .await
+            {
+                Some(error) => {
+                    if events.unbounded_send(Event::Disconnected(error)).is_err() {
+                        return;
+                    }
+                }
+                // the `ReconnectingWebSocket` was dropped
+                None => return,
+            }
+        } else {
+            failures += 1;
+            if config
+                .backoff
+                .max_retries
+                .map_or(false, |max| failures > max)
+            {
+                return;
+            }
+        }
+
+        let delay = config.backoff.delay(failures);
+        timeout(delay).await;
+        if events.is_closed() {
+            return;
+        }
+        ws = match (config.open)(&config.target) {
+            Ok(ws) => ws,
+            Err(_) => return,
+        };
+    }
+}
+
+/// Waits for the connection to open and runs the handshake.
+async fn connect<S>(
+    ws: &mut S,
+    is_open: fn(&S) -> bool,
+    handshake: Option<&Handshake<S>>,
+) -> Result<(), WebSocketError>
+where
+    S: Sink<Message, Error = WebSocketError> + Unpin,
+{
+    poll_fn(|cx| Pin::new(&mut *ws).poll_ready(cx)).await?;
+    if !is_open(ws) {
+        return Err(WebSocketError::ConnectionError);
+    }
+    if let Some(handshake) = handshake {
+        handshake(ws).await?;
+    }
+    Ok(())
+}
+
+/// Forwards messages until the connection is lost, returning the error that ended it.
+///
+/// Returns `None` if the `ReconnectingWebSocket` was dropped.
+async fn serve<S>(
+    ws: &mut S,
+    is_open: fn(&S) -> bool,
+    outbound: &mut mpsc::UnboundedReceiver<Message>,
+    pending: &mut Option<Message>,
+    events: &mpsc::UnboundedSender<Event>,
+) -> Option<WebSocketError>
+where
+    S: Stream<Item = Result<Message, WebSocketError>>
+        + Sink<Message, Error = WebSocketError>
+        + Unpin,
+{
+    let mut error = None;
+    loop {
+        // the browser silently discards messages sent while closing, so keep them for later
+        if is_open(ws) {
+            if let Some(message) = pending.take() {
+                if let Err(e) = send(ws, message).await {
+                    error = Some(e);
+                }
+                continue;
+            }
+        }
+
+        let outbound = if pending.is_none() {
+            Some(&mut *outbound)
+        } else {
+            None
+        };
+        match next_step(ws, outbound).await {
+            Step::Inbound(Some(Ok(message))) => {
+                if events.unbounded_send(Event::Message(message)).is_err() {
+                    return None;
+                }
+            }
+            Step::Inbound(Some(Err(
+                e @ (WebSocketError::ConnectionClose(_) | WebSocketError::HeartbeatTimeout),
+            ))) => return Some(e),
+            Step::Inbound(Some(Err(e))) => error = Some(e),
+            Step::Inbound(None) => return Some(error.unwrap_or(WebSocketError::ConnectionError)),
+            Step::Outbound(Some(message)) => *pending = Some(message),
+            Step::Outbound(None) => return None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{SinkExt, StreamExt};
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[test]
+    fn backoff_delays() {
+        let backoff = Backoff::new(Duration::from_millis(100), Duration::from_secs(1));
+        let delays: Vec<_> = (0..6).map(|n| backoff.delay(n).as_millis()).collect();
+        assert_eq!(delays, [100, 200, 400, 800, 1000, 1000]);
+
+        let backoff = backoff.factor(3);
+        assert_eq!(backoff.delay(1), Duration::from_millis(300));
+        assert_eq!(backoff.delay(u32::MAX), Duration::from_secs(1));
+    }
+
+    #[wasm_bindgen_test]
+    async fn reconnecting_websocket_works() {
+        let ws_echo_server_url =
+            option_env!("WS_ECHO_SERVER_URL").expect("Did you set WS_ECHO_SERVER_URL?");
+
+        let mut ws = ReconnectingWebSocket::builder(ws_echo_server_url)
+            .handshake(|ws| {
+                Box::pin(async move {
+                    // ignore the info the echo-server sends first
+                    let _ = ws.next().await;
+                    ws.send(Message::Text("handshake".to_string())).await
+                })
+            })
+            .build()
+            .unwrap();
+
+        // queued until the handshake is done
+        ws.send(Message::Text("test".to_string())).await.unwrap();
+
+        assert!(matches!(ws.next().await, Some(Event::Connected)));
+        for expected in ["handshake", "test"] {
+            match ws.next().await {
+                Some(Event::Message(Message::Text(text))) => assert_eq!(text, expected),
+                other => panic!("unexpected event: {:?}", other),
+            }
+        }
+    }
+
+    /// Opens in-memory connections, handing the server ends to the test.
+    #[cfg(feature = "mock")]
+    fn memory_builder() -> (
+        ReconnectingWebSocketBuilder<crate::websocket::memory::MemoryWebSocket>,
+        mpsc::UnboundedReceiver<crate::websocket::memory::MemoryWebSocket>,
+    ) {
+        let (servers, receiver) = mpsc::unbounded();
+        let builder = ReconnectingWebSocket::builder_with(move || {
+            let (client, server) = crate::websocket::memory::pair();
+            servers.unbounded_send(server).unwrap();
+            Ok(client)
+        })
+        .backoff(Backoff::new(
+            Duration::from_millis(1),
+            Duration::from_millis(1),
+        ));
+        (builder, receiver)
+    }
+
+    #[cfg(feature = "mock")]
+    #[wasm_bindgen_test]
+    async fn reconnects_over_a_memory_websocket() {
+        let text = |text: &str| Message::Text(text.to_string());
+        let (builder, mut servers) = memory_builder();
+        let mut ws = builder
+            .handshake(|ws| Box::pin(async move { ws.send(Message::Text("hello".into())).await }))
+            .build()
+            .unwrap();
+
+        let mut server = servers.next().await.unwrap();
+        assert!(matches!(ws.next().await, Some(Event::Connected)));
+        assert_eq!(server.next().await.unwrap().unwrap(), text("hello"));
+        ws.send(text("a")).await.unwrap();
+        assert_eq!(server.next().await.unwrap().unwrap(), text("a"));
+        server.send(text("from the server")).await.unwrap();
+        assert!(
+            matches!(ws.next().await, Some(Event::Message(msg)) if msg == text("from the server"))
+        );
+
+        server.close(Some(4000), Some("restart"));
+        match ws.next().await {
+            Some(Event::Disconnected(WebSocketError::ConnectionClose(event))) => {
+                assert_eq!((event.code, event.reason.as_str()), (4000, "restart"))
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+        // queued while disconnected
+        ws.send(text("b")).await.unwrap();
+        ws.send(text("c")).await.unwrap();
+
+        // the handshake runs again before the queued messages are sent
+        let mut server = servers.next().await.unwrap();
+        assert!(matches!(ws.next().await, Some(Event::Connected)));
+        for expected in ["hello", "b", "c"] {
+            assert_eq!(server.next().await.unwrap().unwrap(), text(expected));
+        }
+
+        server.fail();
+        match ws.next().await {
+            Some(Event::Disconnected(WebSocketError::ConnectionClose(event))) => {
+                assert_eq!(event.code, 1006);
+                assert!(!event.was_clean);
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+        let mut server = servers.next().await.unwrap();
+        assert!(matches!(ws.next().await, Some(Event::Connected)));
+        assert_eq!(server.next().await.unwrap().unwrap(), text("hello"));
+    }
+
+    #[cfg(feature = "mock")]
+    #[wasm_bindgen_test]
+    async fn gives_up_after_max_retries() {
+        let (builder, mut servers) = memory_builder();
+        let mut ws = builder
+            .backoff(
+                Backoff::new(Duration::from_millis(1), Duration::from_millis(1)).max_retries(2),
+            )
+            .handshake(|ws| {
+                Box::pin(async move {
+                    match ws.next().await {
+                        Some(Ok(_)) => Ok(()),
+                        Some(Err(e)) => Err(e),
+                        None => Err(WebSocketError::ConnectionError),
+                    }
+                })
+            })
+            .build()
+            .unwrap();
+
+        let mut server = servers.next().await.unwrap();
+        server.send(Message::Text("welcome".into())).await.unwrap();
+        assert!(matches!(ws.next().await, Some(Event::Connected)));
+        server.close(None, None);
+        assert!(matches!(ws.next().await, Some(Event::Disconnected(_))));
+
+        // the next connections close before their handshake completes
+        for _ in 0..3 {
+            servers.next().await.unwrap().close(None, None);
+        }
+        assert!(ws.next().await.is_none());
+        assert!(servers.next().await.is_none());
+    }
+}
@@ -0,0 +1,582 @@
+//! Request/response calls over a [`WebSocket`](crate::websocket::futures::WebSocket).
+//!
+//! [`RpcClient`] sends requests over a single websocket and matches the responses to them by
+//! ID, so that each [`call`](RpcClient::call) resolves to its own result. Messages from the
+//! server that are not responses, such as notifications, are yielded by the [`Notifications`]
+//! stream instead.
+//!
+//! The messages are framed by a [`Framing`]. [JSON-RPC 2.0](https://www.jsonrpc.org/specification)
+//! is supported by [`JsonRpc2`].
+//!
+//! # Example
+//!
+//! ```rust
+//! use gloo_net::websocket::futures::WebSocket;
+//! use gloo_net::websocket::rpc::{RpcClient, RpcError};
+//! use wasm_bindgen_futures::spawn_local;
+//! use futures::StreamExt;
+//!
+//! # macro_rules! console_log {
+//! #    ($($expr:expr),*) => {{}};
+//! # }
+//! # fn no_run() {
+//! let ws = WebSocket::open("wss://api.example.com/rpc").unwrap();
+//! let (client, mut notifications) = RpcClient::new(ws);
+//!
+//! spawn_local(async move {
+//!     match client.call::<_, u64>("add", [1, 2]).await {
+//!         Ok(sum) => console_log!(format!("1 + 2 = {}", sum)),
+//!         Err(RpcError::Server(e)) => console_log!(format!("server error {}: {}", e.code, e.message)),
+//!         Err(e) => console_log!(format!("{}", e)),
+//!     }
+//! });
+//!
+//! spawn_local(async move {
+//!     while let Some(Ok(notification)) = notifications.next().await {
+//!         console_log!(format!("{}: {}", notification.method, notification.params))
+//!     }
+//! })
+//! # }
+//! ```
+use crate::websocket::driver::{next_step, send, Step};
+use crate::websocket::{Message, WebSocketError};
+use crate::Error;
+use futures_channel::{mpsc, oneshot};
+use futures_core::{ready, Stream};
+use futures_sink::Sink;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fmt;
+use std::future::{poll_fn, Future};
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use thiserror::Error as ThisError;
+use wasm_bindgen_futures::spawn_local;
+
+/// The timeout of [`RpcClient::call`] until changed with [`RpcClient::set_timeout`].
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// An error object returned by the server in response to a call.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ErrorObject {
+    /// The error code.
+    pub code: i64,
+    /// A short description of the error.
+    pub message: String,
+    /// Additional information about the error.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl ErrorObject {
+    /// Deserialize the additional information about the error, if there is any.
+    pub fn parse_data<T: DeserializeOwned>(&self) -> Result<Option<T>, Error> {
+        self.data
+            .as_ref()
+            .map(|data| T::deserialize(data).map_err(Error::from))
+            .transpose()
+    }
+}
+
+impl fmt::Display for ErrorObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (code {})", self.message, self.code)
+    }
+}
+
+/// The error of an [`RpcClient`] call.
+#[derive(Debug, ThisError)]
+#[non_exhaustive]
+pub enum RpcError {
+    /// The server responded with an error.
+    #[error("{0}")]
+    Server(ErrorObject),
+    /// No response was received in time.
+    #[error("the call timed out")]
+    Timeout,
+    /// The connection was closed before a response was received.
+    #[error("the connection is closed")]
+    Closed,
+    /// The parameters could not be encoded, or the result decoded.
+    #[error("{0}")]
+    Encoding(#[from] Error),
+}
+
+/// A message received from the server that is not a response to a call.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Notification {
+    /// The method name.
+    pub method: String,
+    /// The parameters, [`Value::Null`] if there are none.
+    pub params: Value,
+}
+
+impl Notification {
+    /// Deserialize the parameters.
+    pub fn parse_params<T: DeserializeOwned>(&self) -> Result<T, Error> {
+        T::deserialize(&self.params).map_err(Error::from)
+    }
+}
+
+/// A message received from the server, as decoded by a [`Framing`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum Incoming {
+    /// The response to the call with the given ID.
+    Response {
+        /// The ID of the call.
+        id: u64,
+        /// The result of the call.
+        result: Result<Value, ErrorObject>,
+    },
+    /// Any other message.
+    Notification(Notification),
+}
+
+/// How calls and responses are represented as websocket messages.
+pub trait Framing {
+    /// Encode a call of `method` with ID `id`. `params` is [`Value::Null`] for no parameters.
+    fn request(id: u64, method: &str, params: Value) -> Result<Message, Error>;
+
+    /// Encode a notification, which is a call without a response.
+    fn notification(method: &str, params: Value) -> Result<Message, Error>;
+
+    /// Decode a message received from the server.
+    fn decode(message: Message) -> Result<Incoming, Error>;
+}
+
+/// The [JSON-RPC 2.0](https://www.jsonrpc.org/specification) framing.
+#[derive(Clone, Copy, Debug)]
+pub struct JsonRpc2;
+
+impl JsonRpc2 {
+    fn encode(id: Option<u64>, method: &str, params: Value) -> Result<Message, Error> {
+        let mut object = Map::new();
+        object.insert("jsonrpc".to_string(), "2.0".into());
+        object.insert("method".to_string(), method.into());
+        if !params.is_null() {
+            object.insert("params".to_string(), params);
+        }
+        if let Some(id) = id {
+            object.insert("id".to_string(), id.into());
+        }
+        Ok(Message::Text(serde_json::to_string(&object)?))
+    }
+}
+
+impl Framing for JsonRpc2 {
+    fn request(id: u64, method: &str, params: Value) -> Result<Message, Error> {
+        Self::encode(Some(id), method, params)
+    }
+
+    fn notification(method: &str, params: Value) -> Result<Message, Error> {
+        Self::encode(None, method, params)
+    }
+
+    fn decode(message: Message) -> Result<Incoming, Error> {
+        let mut object: Map<String, Value> = match message {
+            Message::Text(text) => serde_json::from_str(&text)?,
+            Message::Bytes(bytes) => serde_json::from_slice(&bytes)?,
+        };
+
+        if let Some(method) = object.remove("method") {
+            let method = match method {
+                Value::String(method) => method,
+                _ => return Err(invalid("the method is not a string")),
+            };
+            let params = object.remove("params").unwrap_or(Value::Null);
+            return Ok(Incoming::Notification(Notification { method, params }));
+        }
+
+        let result = match (object.remove("result"), object.remove("error")) {
+            (Some(result), None) => Ok(result),
+            (None, Some(error)) => Err(ErrorObject::deserialize(error)?),
+            _ => return Err(invalid("neither a request nor a response")),
+        };
+        match object.get("id").and_then(Value::as_u64) {
+            Some(id) => Ok(Incoming::Response { id, result }),
+            // e.g. the server could not parse a request, so it could not tell its ID
+            None => Err(match result {
+                Err(error) => Error::GlooError(format!("JSON-RPC error without an ID: {error}")),
+                Ok(_) => invalid("response without an ID"),
+            }),
+        }
+    }
+}
+
+fn invalid(reason: &str) -> Error {
+    Error::GlooError(format!("invalid JSON-RPC 2.0 message: {reason}"))
+}
+
+type Pending = RefCell<HashMap<u64, oneshot::Sender<Result<Value, ErrorObject>>>>;
+
+struct Inner {
+    next_id: Cell<u64>,
+    timeout: Cell<Duration>,
+    pending: Rc<Pending>,
+    outbound: mpsc::UnboundedSender<Message>,
+}
+
+/// A client making calls over a [`WebSocket`](crate::websocket::futures::WebSocket).
+///
+/// It can be cloned to make calls from several tasks over the same connection. The connection is
+/// closed once every clone and the [`Notifications`] stream have been dropped.
+pub struct RpcClient<F = JsonRpc2> {
+    inner: Rc<Inner>,
+    _framing: PhantomData<fn() -> F>,
+}
+
+impl RpcClient<JsonRpc2> {
+    /// Make JSON-RPC 2.0 calls over `ws`.
+    ///
+    /// `ws` is usually a [`WebSocket`](crate::websocket::futures::WebSocket), but any stream and
+    /// sink of [`Message`]s can be used, such as an in-memory websocket in tests. Returns the
+    /// client and the stream of the other messages received.
+    pub fn new<S>(ws: S) -> (Self, Notifications)
+    where
+        S: Stream<Item = Result<Message, WebSocketError>>
+            + Sink<Message, Error = WebSocketError>
+            + Unpin
+            + 'static,
+    {
+        Self::with_framing(ws)
+    }
+}
+
+impl<F: Framing + 'static> RpcClient<F> {
+    /// Make calls framed by `F` over `ws`.
+    ///
+    /// Returns the client and the stream of the other messages received.
+    pub fn with_framing<S>(ws: S) -> (Self, Notifications)
+    where
+        S: Stream<Item = Result<Message, WebSocketError>>
+            + Sink<Message, Error = WebSocketError>
+            + Unpin
+            + 'static,
+    {
+        let pending = Rc::new(Pending::default());
+        let (outbound, outbound_receiver) = mpsc::unbounded();
+        let (notification_sender, receiver) = mpsc::unbounded();
+
+        spawn_local(run::<F, S>(
+            ws,
+            Rc::clone(&pending),
+            outbound_receiver,
+            notification_sender,
+        ));
+
+        let client = Self {
+            inner: Rc::new(Inner {
+                next_id: Cell::new(1),
+                timeout: Cell::new(DEFAULT_TIMEOUT),
+                pending,
+                outbound,
+            }),
+            _framing: PhantomData,
+        };
+        (client, Notifications { receiver })
+    }
+
+    /// Sets the timeout of [`RpcClient::call`], for this client and its clones.
+    pub fn set_timeout(&self, timeout: Duration) {
+        self.inner.timeout.set(timeout);
+    }
+
+    /// Call `method` with `params`, and wait for the result.
+    ///
+    /// Use `()` for a call without parameters. Fails with [`RpcError::Timeout`] if there is no
+    /// response within the timeout, see [`RpcClient::set_timeout`].
+    pub async fn call<P, R>(&self, method: &str, params: P) -> Result<R, RpcError>
+    where
+        P: Serialize,
+        R: DeserializeOwned,
+    {
+        self.call_with_timeout(method, params, self.inner.timeout.get())
+            .await
+    }
+
+    /// Call `method` with `params`, and wait up to `timeout` for the result.
+    pub async fn call_with_timeout<P, R>(
+        &self,
+        method: &str,
+        params: P,
+        timeout: Duration,
+    ) -> Result<R, RpcError>
+    where
+        P: Serialize,
+        R: DeserializeOwned,
+    {
+        let params = serde_json::to_value(params).map_err(Error::from)?;
+        let id = self.inner.next_id.get();
+        self.inner.next_id.set(id.wrapping_add(1));
+        let message = F::request(id, method, params)?;
+
+        let (sender, mut receiver) = oneshot::channel();
+        self.inner.pending.borrow_mut().insert(id, sender);
+        // forgets the call if it times out or is cancelled
+        let _guard = PendingGuard {
+            pending: &self.inner.pending,
+            id,
+        };
+        if self.inner.outbound.unbounded_send(message).is_err() {
+            return Err(RpcError::Closed);
+        }
+
+        let mut timer = crate::timer::timeout(timeout);
+        let result = poll_fn(|cx| {
+            if let Poll::Ready(result) = Pin::new(&mut receiver).poll(cx) {
+                return Poll::Ready(result.map_err(|_| RpcError::Closed));
+            }
+            ready!(Pin::new(&mut timer).poll(cx));
+            Poll::Ready(Err(RpcError::Timeout))
+        })
+        .await?;
+
+        let value = result.map_err(RpcError::Server)?;
+        R::deserialize(value).map_err(|e| RpcError::Encoding(e.into()))
+    }
+
+    /// Send a notification, which is a call without a response.
+    pub fn notify<P: Serialize>(&self, method: &str, params: P) -> Result<(), RpcError> {
+        let params = serde_json::to_value(params).map_err(Error::from)?;
+        let message = F::notification(method, params)?;
+        self.inner
+            .outbound
+            .unbounded_send(message)
+            .map_err(|_| RpcError::Closed)
+    }
+}
+
+impl<F> Clone for RpcClient<F> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Rc::clone(&self.inner),
+            _framing: PhantomData,
+        }
+    }
+}
+
+impl<F> fmt::Debug for RpcClient<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RpcClient")
+            .field("pending_calls", &self.inner.pending.borrow().len())
+            .field("timeout", &self.inner.timeout.get())
+            .field("closed", &self.inner.outbound.is_closed())
+            .finish()
+    }
+}
+
+struct PendingGuard<'a> {
+    pending: &'a Pending,
+    id: u64,
+}
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        self.pending.borrow_mut().remove(&self.id);
+    }
+}
+
+/// The messages received by an [`RpcClient`] that are not responses to its calls.
+///
+/// Messages that cannot be decoded and connection errors are yielded as errors. The stream ends
+/// when the connection is closed.
+pub struct Notifications {
+    receiver: mpsc::UnboundedReceiver<Result<Notification, WebSocketError>>,
+}
+
+impl fmt::Debug for Notifications {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Notifications").finish_non_exhaustive()
+    }
+}
+
+impl Stream for Notifications {
+    type Item = Result<Notification, WebSocketError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
+
+async fn run<F, S>(
+    mut ws: S,
+    pending: Rc<Pending>,
+    mut outbound: mpsc::UnboundedReceiver<Message>,
+    notifications: mpsc::UnboundedSender<Result<Notification, WebSocketError>>,
+) where
+    F: Framing,
+    S: Stream<Item = Result<Message, WebSocketError>>
+        + Sink<Message, Error = WebSocketError>
+        + Unpin,
+{
+    let mut clients_dropped = false;
+    loop {
+        let outbound = if clients_dropped {
+            None
+        } else {
+            Some(&mut outbound)
+        };
+        match next_step(&mut ws, outbound).await {
+            Step::Inbound(Some(Ok(message))) => match F::decode(message) {
+                Ok(Incoming::Response { id, result }) => {
+                    if let Some(sender) = pending.borrow_mut().remove(&id) {
+                        let _ = sender.send(result);
+                    }
+                }
+                Ok(Incoming::Notification(notification)) => {
+                    let _ = notifications.unbounded_send(Ok(notification));
+                }
+                Err(e) => {
+                    let _ = notifications.unbounded_send(Err(WebSocketError::CodecError(e)));
+                }
+            },
+            Step::Inbound(Some(Err(e))) => {
+                let _ = notifications.unbounded_send(Err(e));
+            }
+            Step::Inbound(None) => break,
+            Step::Outbound(Some(message)) => {
+                if let Err(e) = send(&mut ws, message).await {
+                    let _ = notifications.unbounded_send(Err(e));
+                }
+            }
+            Step::Outbound(None) => clients_dropped = true,
+        }
+
+        if clients_dropped && notifications.is_closed() {
+            break;
+        }
+    }
+    // fails the calls still waiting for a response
+    pending.borrow_mut().clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn decode(text: &str) -> Result<Incoming, Error> {
+        JsonRpc2::decode(Message::Text(text.to_string()))
+    }
+
+    #[test]
+    fn json_rpc_requests() {
+        assert_eq!(
+            JsonRpc2::request(7, "add", json!([1, 2])).unwrap(),
+            Message::Text(r#"{"id":7,"jsonrpc":"2.0","method":"add","params":[1,2]}"#.to_string())
+        );
+        assert_eq!(
+            JsonRpc2::notification("ping", Value::Null).unwrap(),
+            Message::Text(r#"{"jsonrpc":"2.0","method":"ping"}"#.to_string())
+        );
+    }
+
+    #[test]
+    fn json_rpc_responses() {
+        assert_eq!(
+            decode(r#"{"jsonrpc":"2.0","id":7,"result":null}"#).unwrap(),
+            Incoming::Response {
+                id: 7,
+                result: Ok(Value::Null)
+            }
+        );
+
+        let error = r#"{"jsonrpc":"2.0","id":8,"error":{"code":-32601,"message":"Method not found","data":{"method":"nope"}}}"#;
+        match decode(error).unwrap() {
+            Incoming::Response {
+                id: 8,
+                result: Err(error),
+            } => {
+                assert_eq!(error.code, -32601);
+                assert_eq!(
+                    error.parse_data::<Value>().unwrap(),
+                    Some(json!({"method": "nope"}))
+                );
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        assert!(decode(
+            r#"{"jsonrpc":"2.0","id":null,"error":{"code":-32700,"message":"Parse error"}}"#
+        )
+        .is_err());
+        assert!(decode(r#"{"jsonrpc":"2.0","id":1}"#).is_err());
+        assert!(decode("[]").is_err());
+    }
+
+    #[test]
+    fn json_rpc_notifications() {
+        assert_eq!(
+            decode(r#"{"jsonrpc":"2.0","method":"update","params":{"value":1}}"#).unwrap(),
+            Incoming::Notification(Notification {
+                method: "update".to_string(),
+                params: json!({"value": 1}),
+            })
+        );
+        // requests from the server are not responses either
+        match decode(r#"{"jsonrpc":"2.0","id":"a","method":"ping"}"#).unwrap() {
+            Incoming::Notification(notification) => {
+                assert_eq!(notification.method, "ping");
+                assert_eq!(notification.params, Value::Null);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[cfg(feature = "mock")]
+    #[wasm_bindgen_test::wasm_bindgen_test]
+    async fn calls_over_a_memory_websocket() {
+        use crate::websocket::memory::pair;
+        use futures::{SinkExt, StreamExt};
+
+        let (client, mut server) = pair();
+        let (rpc, mut notifications) = RpcClient::new(client);
+        spawn_local(async move {
+            while let Some(Ok(Message::Text(text))) = server.next().await {
+                let request: Value = serde_json::from_str(&text).unwrap();
+                if request["method"] == "stop" {
+                    break;
+                }
+                let sum: i64 = request["params"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .filter_map(Value::as_i64)
+                    .sum();
+                let update = json!({"jsonrpc": "2.0", "method": "update", "params": sum});
+                let response = json!({"jsonrpc": "2.0", "id": request["id"], "result": sum});
+                for message in [update, response] {
+                    server
+                        .send(Message::Text(message.to_string()))
+                        .await
+                        .unwrap();
+                }
+            }
+            server.close(Some(4000), Some("done"));
+        });
+
+        assert_eq!(rpc.call::<_, i64>("add", [1, 2]).await.unwrap(), 3);
+        let update = notifications.next().await.unwrap().unwrap();
+        assert_eq!(
+            (update.method.as_str(), update.params),
+            ("update", json!(3))
+        );
+
+        rpc.notify("stop", ()).unwrap();
+        assert!(matches!(
+            notifications.next().await,
+            Some(Err(WebSocketError::ConnectionClose(event))) if event.code == 4000
+        ));
+        assert!(notifications.next().await.is_none());
+        assert!(matches!(
+            rpc.call::<_, i64>("add", [1]).await,
+            Err(RpcError::Closed)
+        ));
+    }
+}
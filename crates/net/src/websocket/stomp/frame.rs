@@ -0,0 +1,423 @@
+//! Encoding and decoding of STOMP 1.2 frames.
+//!
+//! This does not depend on a browser, so it can be used and tested anywhere.
+use crate::websocket::stomp::StompError;
+use std::fmt;
+use std::str::FromStr;
+
+/// The command of a STOMP frame.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Command {
+    /// Sent by the client to open a session.
+    Connect,
+    /// Sent by the client to open a session, the STOMP 1.2 name of `CONNECT`.
+    Stomp,
+    /// Sent by the server when the session is open.
+    Connected,
+    /// Sent by the client to send a message to a destination.
+    Send,
+    /// Sent by the client to subscribe to a destination.
+    Subscribe,
+    /// Sent by the client to end a subscription.
+    Unsubscribe,
+    /// Sent by the client to acknowledge a message.
+    Ack,
+    /// Sent by the client to reject a message.
+    Nack,
+    /// Sent by the client to start a transaction.
+    Begin,
+    /// Sent by the client to commit a transaction.
+    Commit,
+    /// Sent by the client to roll back a transaction.
+    Abort,
+    /// Sent by the client to close the session.
+    Disconnect,
+    /// Sent by the server to deliver a message of a subscription.
+    Message,
+    /// Sent by the server when it has processed a frame that requested a receipt.
+    Receipt,
+    /// Sent by the server when something went wrong.
+    Error,
+}
+
+impl Command {
+    /// The name of the command, as it appears in frames.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Command::Connect => "CONNECT",
+            Command::Stomp => "STOMP",
+            Command::Connected => "CONNECTED",
+            Command::Send => "SEND",
+            Command::Subscribe => "SUBSCRIBE",
+            Command::Unsubscribe => "UNSUBSCRIBE",
+            Command::Ack => "ACK",
+            Command::Nack => "NACK",
+            Command::Begin => "BEGIN",
+            Command::Commit => "COMMIT",
+            Command::Abort => "ABORT",
+            Command::Disconnect => "DISCONNECT",
+            Command::Message => "MESSAGE",
+            Command::Receipt => "RECEIPT",
+            Command::Error => "ERROR",
+        }
+    }
+
+    /// Header values are not escaped in the frames that open a session.
+    fn escapes_headers(&self) -> bool {
+        !matches!(self, Command::Connect | Command::Connected)
+    }
+}
+
+impl FromStr for Command {
+    type Err = StompError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "CONNECT" => Command::Connect,
+            "STOMP" => Command::Stomp,
+            "CONNECTED" => Command::Connected,
+            "SEND" => Command::Send,
+            "SUBSCRIBE" => Command::Subscribe,
+            "UNSUBSCRIBE" => Command::Unsubscribe,
+            "ACK" => Command::Ack,
+            "NACK" => Command::Nack,
+            "BEGIN" => Command::Begin,
+            "COMMIT" => Command::Commit,
+            "ABORT" => Command::Abort,
+            "DISCONNECT" => Command::Disconnect,
+            "MESSAGE" => Command::Message,
+            "RECEIPT" => Command::Receipt,
+            "ERROR" => Command::Error,
+            _ => return Err(protocol(format!("unknown command {s:?}"))),
+        })
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A STOMP frame: a command, headers and a body.
+///
+/// # Example
+///
+/// ```
+/// use gloo_net::websocket::stomp::{Command, Frame};
+///
+/// let frame = Frame::new(Command::Send)
+///     .header("destination", "/queue/a")
+///     .body("hello");
+/// assert_eq!(frame.encode(), b"SEND\ndestination:/queue/a\ncontent-length:5\n\nhello\0");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    /// The command.
+    pub command: Command,
+    /// The headers, in order. When a header is repeated, the first value is the one that counts.
+    pub headers: Vec<(String, String)>,
+    /// The body.
+    pub body: Vec<u8>,
+}
+
+impl Frame {
+    /// Create a frame without headers or body.
+    pub fn new(command: Command) -> Self {
+        Self {
+            command,
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    /// Appends a header.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Sets the body.
+    pub fn body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Gets the value of a header.
+    pub fn get_header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The body as text, if it is valid UTF-8.
+    pub fn text(&self) -> Option<&str> {
+        std::str::from_utf8(&self.body).ok()
+    }
+
+    /// Encode the frame, adding a `content-length` header if it has a body and no such header.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.body.len() + 64);
+        bytes.extend_from_slice(self.command.as_str().as_bytes());
+        bytes.push(b'\n');
+
+        let escape = self.command.escapes_headers();
+        let mut write_header = |name: &str, value: &str| {
+            if escape {
+                escape_into(&mut bytes, name);
+                bytes.push(b':');
+                escape_into(&mut bytes, value);
+            } else {
+                bytes.extend_from_slice(name.as_bytes());
+                bytes.push(b':');
+                bytes.extend_from_slice(value.as_bytes());
+            }
+            bytes.push(b'\n');
+        };
+        for (name, value) in &self.headers {
+            write_header(name, value);
+        }
+        if !self.body.is_empty() && self.get_header("content-length").is_none() {
+            write_header("content-length", &self.body.len().to_string());
+        }
+
+        bytes.push(b'\n');
+        bytes.extend_from_slice(&self.body);
+        bytes.push(0);
+        bytes
+    }
+}
+
+fn escape_into(bytes: &mut Vec<u8>, s: &str) {
+    for b in s.bytes() {
+        match b {
+            b'\\' => bytes.extend_from_slice(b"\\\\"),
+            b'\n' => bytes.extend_from_slice(b"\\n"),
+            b'\r' => bytes.extend_from_slice(b"\\r"),
+            b':' => bytes.extend_from_slice(b"\\c"),
+            b => bytes.push(b),
+        }
+    }
+}
+
+fn unescape(s: &str) -> Result<String, StompError> {
+    let mut unescaped = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        unescaped.push(match chars.next() {
+            Some('\\') => '\\',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('c') => ':',
+            other => return Err(protocol(format!("invalid escape sequence \\{other:?}"))),
+        });
+    }
+    Ok(unescaped)
+}
+
+fn protocol(reason: String) -> StompError {
+    StompError::Protocol(reason)
+}
+
+/// An incremental decoder of STOMP frames.
+///
+/// Bytes are pushed in as they are received, and the frames they complete are returned. Frames
+/// may be split across pushes, and a push may complete several frames. The end-of-line
+/// heart-beats sent between frames are skipped.
+#[derive(Clone, Debug, Default)]
+pub struct FrameDecoder {
+    buffer: Vec<u8>,
+}
+
+impl FrameDecoder {
+    /// Create a new decoder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decodes the given bytes, returning the frames they complete.
+    ///
+    /// After an error, the decoder is reset to an empty state.
+    pub fn push(&mut self, bytes: &[u8]) -> Result<Vec<Frame>, StompError> {
+        self.buffer.extend_from_slice(bytes);
+
+        let mut frames = Vec::new();
+        let mut start = 0;
+        loop {
+            // heart-beats
+            while self.buffer[start..].starts_with(b"\n")
+                || self.buffer[start..].starts_with(b"\r\n")
+            {
+                start += if self.buffer[start] == b'\n' { 1 } else { 2 };
+            }
+            match decode_frame(&self.buffer[start..]) {
+                Ok(Some((frame, length))) => {
+                    frames.push(frame);
+                    start += length;
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    self.buffer.clear();
+                    return Err(e);
+                }
+            }
+        }
+        self.buffer.drain(..start);
+
+        Ok(frames)
+    }
+}
+
+/// Decodes the frame at the start of `bytes`, returning it and its length in bytes, or `None` if
+/// it is incomplete.
+fn decode_frame(bytes: &[u8]) -> Result<Option<(Frame, usize)>, StompError> {
+    let mut position = 0;
+    let mut next_line = || -> Option<&[u8]> {
+        let end = position + bytes[position..].iter().position(|&b| b == b'\n')?;
+        let mut line = &bytes[position..end];
+        if line.last() == Some(&b'\r') {
+            line = &line[..line.len() - 1];
+        }
+        position = end + 1;
+        Some(line)
+    };
+
+    let command = match next_line() {
+        Some(line) => utf8(line)?.parse::<Command>()?,
+        None => return Ok(None),
+    };
+    let mut frame = Frame::new(command);
+    loop {
+        let line = match next_line() {
+            Some(line) => line,
+            None => return Ok(None),
+        };
+        if line.is_empty() {
+            break;
+        }
+        let line = utf8(line)?;
+        let (name, value) = line
+            .split_once(':')
+            .ok_or_else(|| protocol(format!("header without a colon: {line:?}")))?;
+        if command.escapes_headers() {
+            frame.headers.push((unescape(name)?, unescape(value)?));
+        } else {
+            frame.headers.push((name.to_string(), value.to_string()));
+        }
+    }
+
+    let body = &bytes[position..];
+    let length = match frame.get_header("content-length") {
+        Some(length) => {
+            let length: usize = length
+                .parse()
+                .map_err(|_| protocol(format!("invalid content-length {length:?}")))?;
+            if body.len() <= length {
+                return Ok(None);
+            }
+            if body[length] != 0 {
+                return Err(protocol(
+                    "the body is longer than its content-length".into(),
+                ));
+            }
+            length
+        }
+        None => match body.iter().position(|&b| b == 0) {
+            Some(length) => length,
+            None => return Ok(None),
+        },
+    };
+    frame.body = body[..length].to_vec();
+
+    Ok(Some((frame, position + length + 1)))
+}
+
+fn utf8(bytes: &[u8]) -> Result<&str, StompError> {
+    std::str::from_utf8(bytes).map_err(|e| protocol(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode_all(bytes: &[u8]) -> Vec<Frame> {
+        let whole = FrameDecoder::new().push(bytes).unwrap();
+
+        let mut decoder = FrameDecoder::new();
+        let bytewise: Vec<_> = bytes
+            .iter()
+            .flat_map(|&b| decoder.push(&[b]).unwrap())
+            .collect();
+        assert_eq!(whole, bytewise, "chunking changed the result");
+
+        whole
+    }
+
+    #[test]
+    fn round_trip() {
+        let frame = Frame::new(Command::Message)
+            .header("destination", "/topic/a:b")
+            .header("subscription", "sub-0")
+            .header("note", "back\\slash\nnew line")
+            .body(&b"binary\0body"[..]);
+        let encoded = frame.encode();
+        assert!(encoded.starts_with(b"MESSAGE\ndestination:/topic/a\\cb\n"));
+
+        let mut expected = frame;
+        expected
+            .headers
+            .push(("content-length".into(), "11".into()));
+        assert_eq!(decode_all(&encoded), vec![expected]);
+    }
+
+    #[test]
+    fn connect_frames_are_not_escaped() {
+        let frame = Frame::new(Command::Connect).header("passcode", "a:b\\c");
+        assert_eq!(frame.encode(), b"CONNECT\npasscode:a:b\\c\n\n\0");
+        assert_eq!(
+            decode_all(b"CONNECTED\nversion:1.2\nserver:x:1\n\n\0")[0].get_header("server"),
+            Some("x:1")
+        );
+    }
+
+    #[test]
+    fn heart_beats_and_line_endings() {
+        let frames =
+            decode_all(b"\n\r\nRECEIPT\r\nreceipt-id:1\r\n\r\n\0\nERROR\nmessage:bad\n\noops\0\n");
+        assert_eq!(
+            frames,
+            vec![
+                Frame::new(Command::Receipt).header("receipt-id", "1"),
+                Frame::new(Command::Error)
+                    .header("message", "bad")
+                    .body("oops"),
+            ]
+        );
+    }
+
+    #[test]
+    fn repeated_headers() {
+        let frames = decode_all(b"MESSAGE\nfoo:first\nfoo:second\n\n\0");
+        assert_eq!(frames[0].get_header("foo"), Some("first"));
+    }
+
+    #[test]
+    fn invalid_frames() {
+        for bytes in [
+            &b"NOPE\n\n\0"[..],
+            b"SEND\nno colon\n\n\0",
+            b"MESSAGE\nbad:\\t\n\n\0",
+            b"SEND\ncontent-length:1\n\nab\0",
+        ] {
+            let mut decoder = FrameDecoder::new();
+            assert!(decoder.push(bytes).is_err(), "{:?}", bytes);
+            // the decoder recovers
+            assert_eq!(decoder.push(b"RECEIPT\n\n\0").unwrap().len(), 1);
+        }
+    }
+}
@@ -0,0 +1,737 @@
+//! A [STOMP 1.2](https://stomp.github.io/stomp-specification-1.2.html) client over a
+//! [`WebSocket`](crate::websocket::futures::WebSocket).
+//!
+//! [`StompClient::builder`] configures the session, and [`StompClientBuilder::connect`] opens it
+//! over a websocket. The client can then [subscribe](StompClient::subscribe) to destinations,
+//! each subscription being a stream of the `MESSAGE` frames sent to it, and
+//! [send](StompClient::send) messages. Messages of subscriptions that are not in
+//! [`AckMode::Auto`] are acknowledged with [`StompClient::ack`] and [`StompClient::nack`].
+//!
+//! Heart-beats are negotiated with the server when connecting. The client sends its own, and if
+//! the server stops sending its, subscriptions yield [`StompError::HeartbeatTimeout`] and the
+//! connection is closed.
+//!
+//! Frames are encoded and decoded by the [`frame`] module, which does not need a browser.
+//!
+//! # Example
+//!
+//! ```rust
+//! use gloo_net::websocket::futures::WebSocket;
+//! use gloo_net::websocket::stomp::{AckMode, StompClient};
+//! use wasm_bindgen_futures::spawn_local;
+//! use futures::StreamExt;
+//! use std::time::Duration;
+//!
+//! # macro_rules! console_log {
+//! #    ($($expr:expr),*) => {{}};
+//! # }
+//! # fn no_run() {
+//! let ws = WebSocket::open_with_protocol("wss://api.example.com/stomp", "v12.stomp").unwrap();
+//!
+//! spawn_local(async move {
+//!     let client = StompClient::builder()
+//!         .heart_beat(Duration::from_secs(10), Duration::from_secs(10))
+//!         .connect(ws)
+//!         .await
+//!         .unwrap();
+//!
+//!     let mut orders = client.subscribe("/queue/orders", AckMode::ClientIndividual).unwrap();
+//!     client.send("/app/hello", "hello").unwrap();
+//!
+//!     while let Some(Ok(message)) = orders.next().await {
+//!         console_log!(format!("{:?}", message.text()));
+//!         client.ack(&message).unwrap();
+//!     }
+//! })
+//! # }
+//! ```
+use crate::timer::millis;
+use crate::websocket::driver::{next_step_or, send, Step};
+use crate::websocket::{Message, WebSocketError};
+use futures_channel::{mpsc, oneshot};
+use futures_core::Stream;
+use futures_sink::Sink;
+use gloo_timers::future::TimeoutFuture;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fmt;
+use std::future::{poll_fn, Future};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use thiserror::Error as ThisError;
+use wasm_bindgen_futures::spawn_local;
+
+pub mod frame;
+
+pub use frame::{Command, Frame, FrameDecoder};
+
+/// The error of a [`StompClient`].
+#[derive(Debug, ThisError)]
+#[non_exhaustive]
+pub enum StompError {
+    /// The server sent an `ERROR` frame. It closes the connection afterwards.
+    #[error("STOMP server error: {}", .0.get_header("message").unwrap_or("no message"))]
+    Server(Frame),
+    /// A frame could not be decoded, or was not expected.
+    #[error("STOMP protocol error: {0}")]
+    Protocol(String),
+    /// The websocket failed while connecting.
+    #[error("{0}")]
+    WebSocket(#[from] WebSocketError),
+    /// No heart-beat was received from the server in time. The connection is closed.
+    #[error("no heart-beat was received from the STOMP server in time")]
+    HeartbeatTimeout,
+    /// The connection is closed.
+    #[error("the STOMP connection is closed")]
+    Closed,
+}
+
+/// How the messages of a subscription are acknowledged.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum AckMode {
+    /// Messages are acknowledged by the server as soon as they are sent.
+    #[default]
+    Auto,
+    /// Acknowledging a message also acknowledges all previous messages of the subscription.
+    Client,
+    /// Each message is acknowledged on its own.
+    ClientIndividual,
+}
+
+impl AckMode {
+    /// The value of the `ack` header.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AckMode::Auto => "auto",
+            AckMode::Client => "client",
+            AckMode::ClientIndividual => "client-individual",
+        }
+    }
+}
+
+/// Configuration of a STOMP session, created by [`StompClient::builder`].
+#[derive(Clone, Default)]
+pub struct StompClientBuilder {
+    host: Option<String>,
+    credentials: Option<(String, String)>,
+    heart_beat: (Duration, Duration),
+    headers: Vec<(String, String)>,
+}
+
+impl StompClientBuilder {
+    /// Sets the `host` header, the name of the virtual host to connect to.
+    pub fn host(mut self, host: impl Into<String>) -> Self {
+        self.host = Some(host.into());
+        self
+    }
+
+    /// Sets the `login` and `passcode` headers.
+    pub fn credentials(mut self, login: impl Into<String>, passcode: impl Into<String>) -> Self {
+        self.credentials = Some((login.into(), passcode.into()));
+        self
+    }
+
+    /// Offers to send a heart-beat every `outgoing`, and asks the server for one every
+    /// `incoming`. A zero duration disables that direction, which is the default.
+    ///
+    /// The intervals used are the longest of what each side offers and asks for.
+    pub fn heart_beat(mut self, outgoing: Duration, incoming: Duration) -> Self {
+        self.heart_beat = (outgoing, incoming);
+        self
+    }
+
+    /// Adds a header to the `CONNECT` frame.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Opens the session over `ws`, waiting for the server to accept it.
+    ///
+    /// `ws` is usually a [`WebSocket`](crate::websocket::futures::WebSocket), but any stream and
+    /// sink of [`Message`]s can be used, such as an in-memory websocket in tests.
+    pub async fn connect<S>(self, mut ws: S) -> Result<StompClient, StompError>
+    where
+        S: Stream<Item = Result<Message, WebSocketError>>
+            + Sink<Message, Error = WebSocketError>
+            + Unpin
+            + 'static,
+    {
+        let heart_beat = (millis(self.heart_beat.0), millis(self.heart_beat.1));
+        let mut connect = Frame::new(Command::Connect)
+            .header("accept-version", "1.2")
+            .header("heart-beat", format!("{},{}", heart_beat.0, heart_beat.1));
+        if let Some(host) = self.host {
+            connect = connect.header("host", host);
+        }
+        if let Some((login, passcode)) = self.credentials {
+            connect = connect.header("login", login).header("passcode", passcode);
+        }
+        connect.headers.extend(self.headers);
+        send(&mut ws, to_message(&connect)).await?;
+
+        let mut decoder = FrameDecoder::new();
+        let connected = loop {
+            let message = match poll_fn(|cx| Pin::new(&mut ws).poll_next(cx)).await {
+                Some(Ok(message)) => message,
+                Some(Err(e)) => return Err(e.into()),
+                None => return Err(StompError::Closed),
+            };
+            // the server sends nothing else before a subscription is made
+            if let Some(frame) = decoder.push(&message_bytes(message))?.into_iter().next() {
+                break frame;
+            }
+        };
+        match connected.command {
+            Command::Connected => {}
+            Command::Error => return Err(StompError::Server(connected)),
+            command => {
+                return Err(StompError::Protocol(format!(
+                    "expected CONNECTED, received {command}"
+                )))
+            }
+        }
+
+        let heart_beat = negotiate(heart_beat, connected.get_header("heart-beat"));
+        let dispatch = Rc::new(RefCell::new(Dispatch::default()));
+        let (outbound, outbound_receiver) = mpsc::unbounded();
+        spawn_local(run(
+            ws,
+            decoder,
+            Rc::clone(&dispatch),
+            outbound_receiver,
+            heart_beat,
+        ));
+
+        Ok(StompClient {
+            shared: Rc::new(Shared {
+                connected,
+                dispatch,
+                outbound,
+                next_id: Cell::new(0),
+            }),
+        })
+    }
+}
+
+impl fmt::Debug for StompClientBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StompClientBuilder")
+            .field("host", &self.host)
+            .field("login", &self.credentials.as_ref().map(|(login, _)| login))
+            .field("heart_beat", &self.heart_beat)
+            .field("headers", &self.headers)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The outgoing and incoming heart-beat intervals, in milliseconds, agreed on from what the
+/// client asked for and the `heart-beat` header of the `CONNECTED` frame.
+fn negotiate(client: (u32, u32), server: Option<&str>) -> (u32, u32) {
+    let server = server
+        .and_then(|header| header.split_once(','))
+        .and_then(|(sx, sy)| Some((sx.trim().parse().ok()?, sy.trim().parse().ok()?)))
+        .unwrap_or((0, 0));
+    let interval = |ours: u32, theirs: u32| {
+        if ours == 0 || theirs == 0 {
+            0
+        } else {
+            ours.max(theirs)
+        }
+    };
+    (interval(client.0, server.1), interval(client.1, server.0))
+}
+
+fn to_message(frame: &Frame) -> Message {
+    match String::from_utf8(frame.encode()) {
+        Ok(text) => Message::Text(text),
+        Err(e) => Message::Bytes(e.into_bytes()),
+    }
+}
+
+fn message_bytes(message: Message) -> Vec<u8> {
+    match message {
+        Message::Text(text) => text.into_bytes(),
+        Message::Bytes(bytes) => bytes,
+    }
+}
+
+type Messages = mpsc::UnboundedSender<Result<Frame, StompError>>;
+
+/// Where the frames received from the server go.
+#[derive(Default)]
+struct Dispatch {
+    closed: bool,
+    subscriptions: HashMap<String, Messages>,
+    receipts: HashMap<String, oneshot::Sender<Result<(), StompError>>>,
+}
+
+impl Dispatch {
+    fn route(&mut self, frame: Frame) {
+        match frame.command {
+            Command::Message => {
+                let subscription = frame
+                    .get_header("subscription")
+                    .and_then(|id| self.subscriptions.get(id));
+                if let Some(subscription) = subscription {
+                    let _ = subscription.unbounded_send(Ok(frame));
+                }
+            }
+            Command::Receipt => {
+                let receipt = frame
+                    .get_header("receipt-id")
+                    .and_then(|id| self.receipts.remove(id));
+                if let Some(receipt) = receipt {
+                    let _ = receipt.send(Ok(()));
+                }
+            }
+            Command::Error => self.fail(|| StompError::Server(frame.clone())),
+            _ => {}
+        }
+    }
+
+    /// Sends an error to every subscription and pending receipt.
+    fn fail(&mut self, error: impl Fn() -> StompError) {
+        for subscription in self.subscriptions.values() {
+            let _ = subscription.unbounded_send(Err(error()));
+        }
+        for (_, receipt) in self.receipts.drain() {
+            let _ = receipt.send(Err(error()));
+        }
+    }
+
+    /// Ends all subscriptions and pending receipts, current and future.
+    fn close(&mut self) {
+        self.closed = true;
+        self.subscriptions.clear();
+        self.receipts.clear();
+    }
+}
+
+struct Shared {
+    connected: Frame,
+    dispatch: Rc<RefCell<Dispatch>>,
+    outbound: mpsc::UnboundedSender<Message>,
+    next_id: Cell<u64>,
+}
+
+impl Shared {
+    fn next_id(&self, prefix: &str) -> String {
+        let id = self.next_id.get();
+        self.next_id.set(id.wrapping_add(1));
+        format!("{prefix}-{id}")
+    }
+
+    fn send(&self, frame: &Frame) -> Result<(), StompError> {
+        self.outbound
+            .unbounded_send(to_message(frame))
+            .map_err(|_| StompError::Closed)
+    }
+}
+
+/// A STOMP session over a [`WebSocket`](crate::websocket::futures::WebSocket), created with
+/// [`StompClientBuilder::connect`].
+///
+/// It can be cloned to use the session from several places. The connection is closed once the
+/// client, its clones and all its subscriptions have been dropped.
+#[derive(Clone)]
+pub struct StompClient {
+    shared: Rc<Shared>,
+}
+
+impl StompClient {
+    /// Configure a session.
+    pub fn builder() -> StompClientBuilder {
+        StompClientBuilder::default()
+    }
+
+    /// The `CONNECTED` frame sent by the server, with e.g. its `version`, `session` and `server`
+    /// headers.
+    pub fn connected(&self) -> &Frame {
+        &self.shared.connected
+    }
+
+    /// Subscribe to the messages sent to a destination.
+    pub fn subscribe(&self, destination: &str, ack: AckMode) -> Result<Subscription, StompError> {
+        let id = self.shared.next_id("sub");
+        let (sender, receiver) = mpsc::unbounded();
+        {
+            let mut dispatch = self.shared.dispatch.borrow_mut();
+            if dispatch.closed {
+                return Err(StompError::Closed);
+            }
+            dispatch.subscriptions.insert(id.clone(), sender);
+        }
+
+        let subscription = Subscription {
+            shared: Rc::clone(&self.shared),
+            id,
+            destination: destination.to_string(),
+            receiver,
+        };
+        self.shared.send(
+            &Frame::new(Command::Subscribe)
+                .header("id", subscription.id.as_str())
+                .header("destination", destination)
+                .header("ack", ack.as_str()),
+        )?;
+        Ok(subscription)
+    }
+
+    /// Send a message to a destination.
+    pub fn send(&self, destination: &str, body: impl Into<Vec<u8>>) -> Result<(), StompError> {
+        self.send_frame(
+            Frame::new(Command::Send)
+                .header("destination", destination)
+                .body(body),
+        )
+    }
+
+    /// Send a frame, e.g. a `SEND` frame with a `content-type` header.
+    pub fn send_frame(&self, frame: Frame) -> Result<(), StompError> {
+        self.shared.send(&frame)
+    }
+
+    /// Send a frame with a `receipt` header, and wait for the server to confirm it was processed.
+    pub async fn send_with_receipt(&self, frame: Frame) -> Result<(), StompError> {
+        let id = self.shared.next_id("receipt");
+        let (sender, receiver) = oneshot::channel();
+        {
+            let mut dispatch = self.shared.dispatch.borrow_mut();
+            if dispatch.closed {
+                return Err(StompError::Closed);
+            }
+            dispatch.receipts.insert(id.clone(), sender);
+        }
+
+        self.shared.send(&frame.header("receipt", id))?;
+        receiver.await.unwrap_or(Err(StompError::Closed))
+    }
+
+    /// Acknowledge a message received by a subscription in [`AckMode::Client`] or
+    /// [`AckMode::ClientIndividual`].
+    pub fn ack(&self, message: &Frame) -> Result<(), StompError> {
+        self.acknowledge(Command::Ack, message)
+    }
+
+    /// Tell the server a message received by a subscription in [`AckMode::Client`] or
+    /// [`AckMode::ClientIndividual`] was not consumed.
+    pub fn nack(&self, message: &Frame) -> Result<(), StompError> {
+        self.acknowledge(Command::Nack, message)
+    }
+
+    fn acknowledge(&self, command: Command, message: &Frame) -> Result<(), StompError> {
+        let id = message.get_header("ack").ok_or_else(|| {
+            StompError::Protocol("the message has no ack header, its subscription is auto".into())
+        })?;
+        self.shared.send(&Frame::new(command).header("id", id))
+    }
+
+    /// End the session, waiting for the server to confirm it received everything sent before.
+    pub async fn disconnect(self) -> Result<(), StompError> {
+        self.send_with_receipt(Frame::new(Command::Disconnect))
+            .await
+    }
+}
+
+impl fmt::Debug for StompClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StompClient")
+            .field("connected", &self.shared.connected)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The messages sent to a destination, returned by [`StompClient::subscribe`].
+///
+/// It yields the `MESSAGE` frames of the subscription, and the errors that end the session. The
+/// stream ends when the connection is closed. Dropping it unsubscribes.
+pub struct Subscription {
+    shared: Rc<Shared>,
+    id: String,
+    destination: String,
+    receiver: mpsc::UnboundedReceiver<Result<Frame, StompError>>,
+}
+
+impl Subscription {
+    /// The `id` header of the subscription.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The destination subscribed to.
+    pub fn destination(&self) -> &str {
+        &self.destination
+    }
+}
+
+impl fmt::Debug for Subscription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Subscription")
+            .field("id", &self.id)
+            .field("destination", &self.destination)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Stream for Subscription {
+    type Item = Result<Frame, StompError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        let removed = self
+            .shared
+            .dispatch
+            .borrow_mut()
+            .subscriptions
+            .remove(&self.id);
+        if removed.is_some() {
+            let _ = self
+                .shared
+                .send(&Frame::new(Command::Unsubscribe).header("id", self.id.as_str()));
+        }
+    }
+}
+
+/// The heart-beat timers of the background task.
+enum HeartBeat {
+    Send,
+    Check,
+}
+
+fn poll_timer(timer: &mut Option<TimeoutFuture>, cx: &mut Context<'_>) -> bool {
+    match timer {
+        Some(timer) => Pin::new(timer).poll(cx).is_ready(),
+        None => false,
+    }
+}
+
+fn timer(millis: u32) -> Option<TimeoutFuture> {
+    (millis > 0).then(|| TimeoutFuture::new(millis))
+}
+
+async fn run<S>(
+    mut ws: S,
+    mut decoder: FrameDecoder,
+    dispatch: Rc<RefCell<Dispatch>>,
+    mut outbound: mpsc::UnboundedReceiver<Message>,
+    (outgoing, incoming): (u32, u32),
+) where
+    S: Stream<Item = Result<Message, WebSocketError>>
+        + Sink<Message, Error = WebSocketError>
+        + Unpin,
+{
+    // the server is allowed some lateness, so silence is only checked every two intervals
+    let incoming = incoming.saturating_mul(2);
+    let mut send_timer = timer(outgoing);
+    let mut check_timer = timer(incoming);
+    let mut received = false;
+
+    loop {
+        let step = next_step_or(&mut ws, Some(&mut outbound), |cx| {
+            if poll_timer(&mut send_timer, cx) {
+                return Poll::Ready(HeartBeat::Send);
+            }
+            if poll_timer(&mut check_timer, cx) {
+                return Poll::Ready(HeartBeat::Check);
+            }
+            Poll::Pending
+        })
+        .await;
+
+        let message = match step {
+            Ok(Step::Inbound(Some(Ok(message)))) => {
+                received = true;
+                let frames = decoder.push(&message_bytes(message));
+                match frames {
+                    Ok(frames) => {
+                        let mut dispatch = dispatch.borrow_mut();
+                        for frame in frames {
+                            dispatch.route(frame);
+                        }
+                    }
+                    Err(StompError::Protocol(reason)) => {
+                        dispatch
+                            .borrow_mut()
+                            .fail(|| StompError::Protocol(reason.clone()));
+                        break;
+                    }
+                    Err(_) => break,
+                }
+                continue;
+            }
+            // the stream ends after the close event
+            Ok(Step::Inbound(Some(Err(_)))) => continue,
+            Ok(Step::Inbound(None)) => break,
+            Ok(Step::Outbound(Some(message))) => message,
+            // the client and all subscriptions were dropped
+            Ok(Step::Outbound(None)) => break,
+            Err(HeartBeat::Send) => Message::Text("\n".to_string()),
+            Err(HeartBeat::Check) => {
+                if !received {
+                    dispatch.borrow_mut().fail(|| StompError::HeartbeatTimeout);
+                    break;
+                }
+                received = false;
+                check_timer = timer(incoming);
+                continue;
+            }
+        };
+
+        if send(&mut ws, message).await.is_err() {
+            break;
+        }
+        // any frame counts as a heart-beat
+        send_timer = timer(outgoing);
+    }
+
+    dispatch.borrow_mut().close();
+    // dropping the websocket closes it
+    drop(ws);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    #[test]
+    fn heart_beats_are_negotiated() {
+        assert_eq!(negotiate((1000, 2000), Some("3000,500")), (1000, 3000));
+        assert_eq!(negotiate((1000, 2000), Some("0,0")), (0, 0));
+        assert_eq!(negotiate((0, 2000), Some("100,100")), (0, 2000));
+        assert_eq!(negotiate((1000, 1000), None), (0, 0));
+        assert_eq!(negotiate((1000, 1000), Some("garbage")), (0, 0));
+    }
+
+    #[test]
+    fn frames_are_dispatched() {
+        let mut dispatch = Dispatch::default();
+        let (sender, mut messages) = mpsc::unbounded();
+        dispatch.subscriptions.insert("sub-0".to_string(), sender);
+        let (sender, mut receipt) = oneshot::channel();
+        dispatch.receipts.insert("receipt-1".to_string(), sender);
+
+        let message = Frame::new(Command::Message)
+            .header("subscription", "sub-0")
+            .body("hi");
+        dispatch.route(message.clone());
+        dispatch.route(Frame::new(Command::Message).header("subscription", "sub-9"));
+        assert_eq!(messages.try_next().unwrap().unwrap().unwrap(), message);
+        assert!(messages.try_next().is_err());
+
+        dispatch.route(Frame::new(Command::Receipt).header("receipt-id", "receipt-1"));
+        assert!(matches!(receipt.try_recv(), Ok(Some(Ok(())))));
+
+        dispatch.route(Frame::new(Command::Error).header("message", "bad"));
+        let error = messages.try_next().unwrap().unwrap().unwrap_err();
+        assert_eq!(error.to_string(), "STOMP server error: bad");
+
+        dispatch.close();
+        assert!(futures::executor::block_on(messages.next()).is_none());
+    }
+
+    #[cfg(feature = "mock")]
+    #[wasm_bindgen_test::wasm_bindgen_test]
+    async fn session_over_a_memory_websocket() {
+        use crate::websocket::memory::{pair, MemoryWebSocket};
+        use futures::SinkExt;
+
+        async fn receive(server: &mut MemoryWebSocket) -> Frame {
+            let message = server.next().await.unwrap().unwrap();
+            FrameDecoder::new()
+                .push(&message_bytes(message))
+                .unwrap()
+                .remove(0)
+        }
+
+        // a rejected CONNECT
+        let (ws, mut server) = pair();
+        let (result, ()) = futures::join!(StompClient::builder().connect(ws), async {
+            receive(&mut server).await;
+            let error = Frame::new(Command::Error).header("message", "bad login");
+            server.send(to_message(&error)).await.unwrap();
+        });
+        match result {
+            Err(StompError::Server(frame)) => {
+                assert_eq!(frame.get_header("message"), Some("bad login"))
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+
+        let (ws, mut server) = pair();
+        let (client, ()) = futures::join!(
+            StompClient::builder()
+                .host("broker")
+                .credentials("user", "secret")
+                .connect(ws),
+            async {
+                let connect = receive(&mut server).await;
+                assert_eq!(connect.command, Command::Connect);
+                assert_eq!(connect.get_header("accept-version"), Some("1.2"));
+                assert_eq!(connect.get_header("host"), Some("broker"));
+                assert_eq!(connect.get_header("login"), Some("user"));
+                assert_eq!(connect.get_header("heart-beat"), Some("0,0"));
+                let connected = Frame::new(Command::Connected).header("version", "1.2");
+                server.send(to_message(&connected)).await.unwrap();
+            }
+        );
+        let client = client.unwrap();
+        assert_eq!(client.connected().get_header("version"), Some("1.2"));
+
+        let mut orders = client
+            .subscribe("/queue/orders", AckMode::ClientIndividual)
+            .unwrap();
+        let subscribe = receive(&mut server).await;
+        assert_eq!(
+            subscribe,
+            Frame::new(Command::Subscribe)
+                .header("id", "sub-0")
+                .header("destination", "/queue/orders")
+                .header("ack", "client-individual")
+        );
+
+        let message = Frame::new(Command::Message)
+            .header("subscription", "sub-0")
+            .header("ack", "m-1")
+            .body("hi");
+        server.send(to_message(&message)).await.unwrap();
+        let received = orders.next().await.unwrap().unwrap();
+        assert_eq!(received.get_header("content-length"), Some("2"));
+        assert_eq!(received.text(), Some("hi"));
+        client.ack(&received).unwrap();
+        assert_eq!(
+            receive(&mut server).await,
+            Frame::new(Command::Ack).header("id", "m-1")
+        );
+
+        drop(orders);
+        assert_eq!(
+            receive(&mut server).await,
+            Frame::new(Command::Unsubscribe).header("id", "sub-0")
+        );
+
+        // the server closes the connection after an ERROR frame
+        let mut news = client.subscribe("/topic/news", AckMode::Auto).unwrap();
+        receive(&mut server).await;
+        let error = Frame::new(Command::Error).header("message", "malformed");
+        server.send(to_message(&error)).await.unwrap();
+        server.close(Some(1000), None);
+        match news.next().await {
+            Some(Err(StompError::Server(frame))) => assert_eq!(frame, error),
+            other => panic!("unexpected {other:?}"),
+        }
+        assert!(news.next().await.is_none());
+        assert!(matches!(
+            client.subscribe("/topic/news", AckMode::Auto),
+            Err(StompError::Closed)
+        ));
+    }
+}
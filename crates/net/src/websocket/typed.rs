@@ -0,0 +1,294 @@
+//! Typed messages over a [`WebSocket`].
+//!
+//! [`TypedWebSocket`] wraps a [`WebSocket`], encoding the values sent into it and decoding the
+//! messages received from it with a [`Codec`]. Codecs are provided for JSON text messages and
+//! for CBOR and MessagePack binary messages, behind the `json`, `cbor` and `msgpack` features.
+//!
+//! # Example
+//!
+//! ```rust
+//! use gloo_net::websocket::futures::WebSocket;
+//! use gloo_net::websocket::typed::{Json, TypedWebSocket};
+//! use serde::{Deserialize, Serialize};
+//! use wasm_bindgen_futures::spawn_local;
+//! use futures::{SinkExt, StreamExt};
+//!
+//! #[derive(Serialize)]
+//! struct Subscribe {
+//!     channel: String,
+//! }
+//!
+//! #[derive(Deserialize)]
+//! struct Update {
+//!     channel: String,
+//!     value: u32,
+//! }
+//!
+//! # fn no_run() {
+//! let ws = WebSocket::open("wss://api.example.com/updates").unwrap();
+//! let mut ws = TypedWebSocket::<Update, Subscribe, Json>::new(ws);
+//!
+//! spawn_local(async move {
+//!     ws.send(Subscribe { channel: "prices".to_string() }).await.unwrap();
+//!     while let Some(update) = ws.next().await {
+//!         match update {
+//!             Ok(update) => { /* ... */ }
+//!             // a message that could not be decoded, the stream goes on
+//!             Err(e) => { /* ... */ }
+//!         }
+//!     }
+//! })
+//! # }
+//! ```
+use crate::websocket::futures::WebSocket;
+use crate::websocket::{Message, WebSocketError};
+use crate::Error;
+use futures_core::{ready, Stream};
+use futures_sink::Sink;
+use pin_project::pin_project;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Encodes values into WebSocket messages and decodes them back.
+pub trait Codec {
+    /// Encode a value into a message.
+    fn encode<T>(value: &T) -> Result<Message, Error>
+    where
+        T: Serialize;
+
+    /// Decode a message into a value.
+    fn decode<T>(message: Message) -> Result<T, Error>
+    where
+        T: DeserializeOwned;
+}
+
+/// Encodes values as JSON in text messages.
+///
+/// Both text and binary messages are decoded.
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+#[derive(Clone, Copy, Debug)]
+pub struct Json;
+
+#[cfg(feature = "json")]
+impl Codec for Json {
+    fn encode<T>(value: &T) -> Result<Message, Error>
+    where
+        T: Serialize,
+    {
+        Ok(Message::Text(serde_json::to_string(value)?))
+    }
+
+    fn decode<T>(message: Message) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+    {
+        match message {
+            Message::Text(text) => serde_json::from_str(&text),
+            Message::Bytes(bytes) => serde_json::from_slice(&bytes),
+        }
+        .map_err(Error::from)
+    }
+}
+
+/// Encodes values as CBOR in binary messages.
+#[cfg(feature = "cbor")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cbor")))]
+#[derive(Clone, Copy, Debug)]
+pub struct Cbor;
+
+#[cfg(feature = "cbor")]
+impl Codec for Cbor {
+    fn encode<T>(value: &T) -> Result<Message, Error>
+    where
+        T: Serialize,
+    {
+        let mut cbor = Vec::new();
+        ciborium::into_writer(value, &mut cbor)?;
+        Ok(Message::Bytes(cbor))
+    }
+
+    fn decode<T>(message: Message) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+    {
+        ciborium::from_reader(binary(&message)?).map_err(Error::from)
+    }
+}
+
+/// Encodes values as MessagePack in binary messages.
+#[cfg(feature = "msgpack")]
+#[cfg_attr(docsrs, doc(cfg(feature = "msgpack")))]
+#[derive(Clone, Copy, Debug)]
+pub struct MessagePack;
+
+#[cfg(feature = "msgpack")]
+impl Codec for MessagePack {
+    fn encode<T>(value: &T) -> Result<Message, Error>
+    where
+        T: Serialize,
+    {
+        Ok(Message::Bytes(rmp_serde::to_vec_named(value)?))
+    }
+
+    fn decode<T>(message: Message) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+    {
+        rmp_serde::from_slice(binary(&message)?).map_err(Error::from)
+    }
+}
+
+#[cfg(any(feature = "cbor", feature = "msgpack"))]
+fn binary(message: &Message) -> Result<&[u8], Error> {
+    match message {
+        Message::Bytes(bytes) => Ok(bytes),
+        Message::Text(_) => Err(Error::GlooError(
+            "expected a binary message, received a text message".to_string(),
+        )),
+    }
+}
+
+/// A [`WebSocket`] that receives values of type `In` and sends values of type `Out`, encoded
+/// with the codec `C`.
+///
+/// Any other stream and sink of [`Message`]s can be wrapped instead of a [`WebSocket`], such as
+/// an in-memory websocket in tests.
+///
+/// A message that fails to decode yields a [`WebSocketError::CodecError`] and the stream
+/// continues with the next message. Other errors are reported as the underlying [`WebSocket`]
+/// reports them.
+#[pin_project]
+pub struct TypedWebSocket<In, Out, C, S = WebSocket> {
+    #[pin]
+    ws: S,
+    _marker: PhantomData<fn(Out) -> (In, C)>,
+}
+
+impl<In, Out, C, S> TypedWebSocket<In, Out, C, S> {
+    /// Wrap a WebSocket.
+    pub fn new(ws: S) -> Self {
+        Self {
+            ws,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The wrapped WebSocket.
+    pub fn get_ref(&self) -> &S {
+        &self.ws
+    }
+
+    /// The wrapped WebSocket.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.ws
+    }
+
+    /// Unwrap the WebSocket.
+    pub fn into_inner(self) -> S {
+        self.ws
+    }
+}
+
+impl<In, Out, C> From<WebSocket> for TypedWebSocket<In, Out, C> {
+    fn from(ws: WebSocket) -> Self {
+        Self::new(ws)
+    }
+}
+
+impl<In, Out, C, S> fmt::Debug for TypedWebSocket<In, Out, C, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TypedWebSocket").finish_non_exhaustive()
+    }
+}
+
+impl<In, Out, C, S> Stream for TypedWebSocket<In, Out, C, S>
+where
+    In: DeserializeOwned,
+    C: Codec,
+    S: Stream<Item = Result<Message, WebSocketError>>,
+{
+    type Item = Result<In, WebSocketError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let item = ready!(self.project().ws.poll_next(cx));
+        Poll::Ready(item.map(|item| {
+            item.and_then(|message| C::decode(message).map_err(WebSocketError::CodecError))
+        }))
+    }
+}
+
+impl<In, Out, C, S> Sink<Out> for TypedWebSocket<In, Out, C, S>
+where
+    Out: Serialize,
+    C: Codec,
+    S: Sink<Message, Error = WebSocketError>,
+{
+    type Error = WebSocketError;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.project().ws.poll_ready(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: Out) -> Result<(), Self::Error> {
+        let message = C::encode(&item).map_err(WebSocketError::CodecError)?;
+        self.project().ws.start_send(message)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.project().ws.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.project().ws.poll_close(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    fn round_trip<C: Codec>() {
+        let message = C::encode(&Point { x: 1, y: -2 }).unwrap();
+        assert_eq!(C::decode::<Point>(message).unwrap(), Point { x: 1, y: -2 });
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_codec() {
+        round_trip::<Json>();
+        assert_eq!(
+            Json::encode(&Point { x: 1, y: 2 }).unwrap(),
+            Message::Text(r#"{"x":1,"y":2}"#.to_string())
+        );
+        assert!(Json::decode::<Point>(Message::Text("{}".to_string())).is_err());
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn cbor_codec() {
+        round_trip::<Cbor>();
+        assert!(matches!(
+            Cbor::decode::<Point>(Message::Text("{}".to_string())),
+            Err(Error::GlooError(_))
+        ));
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn msgpack_codec() {
+        round_trip::<MessagePack>();
+        assert!(MessagePack::decode::<Point>(Message::Bytes(vec![0xc1])).is_err());
+    }
+}
@@ -138,3 +138,104 @@ async fn query_preserve_duplicate_params() {
         .unwrap();
     assert_eq!(resp.url(), format!("{}/get?q=1&q=2", *HTTPBIN_URL));
 }
+
+#[wasm_bindgen_test]
+async fn error_for_status() {
+    let resp = Request::get(&format!("{}/status/404", *HTTPBIN_URL))
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.status_code().is_client_error());
+
+    match resp.error_for_status().await {
+        Err(gloo_net::Error::Http { status, .. }) => assert_eq!(status, 404),
+        other => panic!("expected an HTTP error, got {:?}", other),
+    }
+
+    let resp = Request::get(&format!("{}/get", *HTTPBIN_URL))
+        .send()
+        .await
+        .unwrap()
+        .error_for_status()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+}
+
+#[cfg(feature = "http-interop")]
+#[wasm_bindgen_test]
+async fn http_interop_round_trip() {
+    use std::convert::TryFrom;
+
+    let req = http::Request::post(format!("{}/anything", *HTTPBIN_URL))
+        .header("X-Custom", "value")
+        .body(b"body".to_vec())
+        .unwrap();
+    let resp = Request::try_from(req).unwrap().send().await.unwrap();
+
+    let resp = resp.into_http().await.unwrap();
+    assert_eq!(resp.status(), http::StatusCode::OK);
+
+    #[derive(Deserialize, Debug)]
+    struct HttpBin {
+        data: String,
+    }
+    let json: HttpBin = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(json.data, "body");
+}
+
+#[cfg(all(feature = "cbor", feature = "msgpack"))]
+#[wasm_bindgen_test]
+async fn binary_formats_round_trip() {
+    use gloo_net::http::Response;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Payload {
+        data: String,
+        num: i16,
+    }
+
+    let payload = Payload {
+        data: "data".to_string(),
+        num: 42,
+    };
+
+    let resp = Response::builder().cbor(&payload).unwrap();
+    assert_eq!(
+        resp.headers().get("Content-Type").as_deref(),
+        Some("application/cbor")
+    );
+    assert_eq!(resp.cbor::<Payload>().await.unwrap(), payload);
+
+    let resp = Response::builder().msgpack(&payload).unwrap();
+    assert_eq!(resp.msgpack::<Payload>().await.unwrap(), payload);
+
+    let resp = Response::builder().body(Some("not msgpack")).unwrap();
+    assert!(matches!(
+        resp.msgpack::<Payload>().await,
+        Err(gloo_net::Error::MsgpackDeError(_))
+    ));
+}
+
+#[wasm_bindgen_test]
+async fn json_lines() {
+    use futures::StreamExt;
+    use gloo_net::http::Response;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Line {
+        n: u32,
+    }
+
+    let resp = Response::builder()
+        .body(Some("{\"n\":1}\n\n{\"n\":2}\r\nnot json\n{\"n\":3}"))
+        .unwrap();
+    let lines: Vec<_> = resp.json_lines::<Line>().unwrap().collect().await;
+    assert!(resp.json_lines::<Line>().is_err());
+
+    assert_eq!(lines.len(), 4);
+    assert_eq!(lines[0].as_ref().unwrap(), &Line { n: 1 });
+    assert_eq!(lines[1].as_ref().unwrap(), &Line { n: 2 });
+    assert!(lines[2].is_err());
+    assert_eq!(lines[3].as_ref().unwrap(), &Line { n: 3 });
+}
@@ -0,0 +1,110 @@
+#![cfg(all(feature = "http", feature = "mock"))]
+
+use gloo_net::http::mock::MockTransport;
+use gloo_net::http::{Method, Request, Response};
+use gloo_net::Error;
+use wasm_bindgen_test::*;
+
+#[cfg(feature = "browser-test")]
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+async fn mock_routes_requests() {
+    let mock = MockTransport::new();
+    mock.on(Method::GET, "/users/*")
+        .header("Accept", "application/json")
+        .respond_with(|_| {
+            Response::builder()
+                .status(200)
+                .json(&vec!["ferris".to_string()])
+        });
+    mock.on(Method::POST, "/users").respond_with(|req| {
+        Response::builder()
+            .status(201)
+            .body(Some(req.text().as_str()))
+    });
+    let _guard = mock.install();
+
+    let resp = Request::get("/users/42")
+        .header("Accept", "application/json")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.json::<Vec<String>>().await.unwrap(), vec!["ferris"]);
+
+    let resp = Request::post("/users")
+        .body("ferris")
+        .unwrap()
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 201);
+    assert_eq!(resp.text().await.unwrap(), "ferris");
+
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].method, Method::GET);
+    assert_eq!(requests[0].header("accept"), Some("application/json"));
+    assert_eq!(requests[1].text(), "ferris");
+}
+
+#[wasm_bindgen_test]
+async fn mock_unmatched_and_errors() {
+    let mock = MockTransport::new();
+    mock.on(Method::GET, "/offline")
+        .respond_with(|_| Err(Error::GlooError("network down".to_string())));
+    let _guard = mock.install();
+
+    assert!(matches!(
+        Request::get("/offline").send().await,
+        Err(Error::GlooError(message)) if message == "network down"
+    ));
+    assert!(Request::get("/unknown").send().await.is_err());
+    assert_eq!(mock.requests().len(), 2);
+}
+
+#[cfg(feature = "eventsource")]
+#[wasm_bindgen_test]
+async fn fetch_event_source_reconnects() {
+    use futures::StreamExt;
+    use gloo_net::eventsource::fetch::FetchEventSource;
+    use gloo_net::eventsource::EventSourceError;
+
+    let mock = MockTransport::new();
+    mock.on(Method::POST, "/events").respond_with(|req| {
+        match req.header("Last-Event-ID") {
+            // the second connection is told to stop reconnecting
+            Some(_) => Response::builder().status(204).body(None as Option<&str>),
+            None => Response::builder()
+                .header("Content-Type", "text/event-stream")
+                .body(Some(
+                    "retry: 10\n\n: comment\nevent: update\ndata: a\ndata: b\nid: 2\n\ndata: c\n\n",
+                )),
+        }
+    });
+    let _guard = mock.install();
+
+    let request = Request::post("/events").header("Authorization", "Bearer token");
+    let mut es = FetchEventSource::new(request).unwrap();
+
+    let (event_type, message) = es.next().await.unwrap().unwrap();
+    assert_eq!(event_type, "update");
+    assert_eq!(message.data().as_string().unwrap(), "a\nb");
+    assert_eq!(message.last_event_id(), "2");
+
+    let (event_type, message) = es.next().await.unwrap().unwrap();
+    assert_eq!(event_type, "message");
+    assert_eq!(message.data().as_string().unwrap(), "c");
+
+    assert_eq!(
+        es.next().await,
+        Some(Err(EventSourceError::ConnectionError))
+    );
+    assert_eq!(es.next().await, None);
+
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].header("Authorization"), Some("Bearer token"));
+    assert_eq!(requests[0].header("Accept"), Some("text/event-stream"));
+    assert_eq!(requests[1].header("Last-Event-ID"), Some("2"));
+}
@@ -46,3 +46,23 @@ fn query_params_escape() {
     params.append("key", "ab&c");
     assert_eq!(params.to_string(), "a=1&key=ab%26c");
 }
+
+#[cfg(feature = "urlencoded")]
+#[wasm_bindgen_test]
+fn query_params_serde() {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Search {
+        q: String,
+        tag: Vec<String>,
+    }
+
+    let search = Search {
+        q: "ab&c".to_string(),
+        tag: vec!["x".to_string(), "y".to_string()],
+    };
+    let params = QueryParams::from_serde(&search).unwrap();
+    assert_eq!(params.to_string(), "q=ab%26c&tag=x&tag=y");
+    assert_eq!(params.to_serde::<Search>().unwrap(), search);
+}